@@ -0,0 +1,121 @@
+use super::solution::Answer;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+pub const ANSWERS_FILE_NAME: &str = "answers.txt";
+
+/// Recorded answers, keyed by (day, part).
+///
+/// The file has one "<day> <part> <answer>" line per answer. Newlines in grid answers
+/// are stored as "\n" so each answer stays on a single line. Empty lines and lines
+/// starting with '#' are ignored.
+#[derive(Default)]
+pub struct KnownAnswers {
+    entries: BTreeMap<(u32, u32), String>,
+}
+
+pub enum Check<'a> {
+    Match,
+    Mismatch { expected: &'a str },
+    Missing,
+}
+
+/// Encodes an answer as it is stored in the answers file.
+pub fn encode(answer: &Answer) -> String {
+    let text = match answer {
+        Answer::Grid(grid) => grid.trim_end().to_string(),
+        _ => answer.to_string(),
+    };
+
+    let mut encoded = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => encoded.push_str("\\\\"),
+            '\n' => encoded.push_str("\\n"),
+            '\r' => {},
+            _ => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+impl KnownAnswers {
+    pub fn parse(text: &str) -> Result<KnownAnswers, String> {
+        let mut answers = KnownAnswers::default();
+
+        for (line_idx, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut toks = line.splitn(3, ' ');
+            let (day, part, answer) = match (toks.next(), toks.next(), toks.next()) {
+                (Some(day), Some(part), Some(answer)) => (day, part, answer),
+                _ => return Err(format!("Line {}: expected '<day> <part> <answer>'", line_idx + 1)),
+            };
+
+            let day = day.parse::<u32>()
+                .map_err(|_| format!("Line {}: invalid day '{}'", line_idx + 1, day))?;
+            let part = match part {
+                "1" => 1,
+                "2" => 2,
+                _ => return Err(format!("Line {}: invalid part '{}'", line_idx + 1, part)),
+            };
+
+            answers.entries.insert((day, part), answer.to_string());
+        }
+
+        Ok(answers)
+    }
+
+    pub fn check(&self, day: u32, part: u32, answer: &Answer) -> Check<'_> {
+        match self.entries.get(&(day, part)) {
+            Some(expected) if *expected == encode(answer) => Check::Match,
+            Some(expected) => Check::Mismatch { expected },
+            None => Check::Missing,
+        }
+    }
+
+    pub fn record(&mut self, day: u32, part: u32, answer: &Answer) {
+        self.entries.insert((day, part), encode(answer));
+    }
+
+    /// Serializes the answers, sorted by day and part.
+    pub fn to_file_string(&self) -> String {
+        let mut text = String::new();
+        for ((day, part), answer) in &self.entries {
+            writeln!(text, "{} {} {}", day, part, answer).unwrap();
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut answers = KnownAnswers::default();
+        answers.record(13, 2, &Answer::Grid("* *\n * \n".to_string()));
+        answers.record(1, 1, &Answer::Int(1374));
+        answers.record(25, 2, &Answer::None);
+
+        let text = answers.to_file_string();
+        assert_eq!("1 1 1374\n13 2 * *\\n *\n25 2 -\n", text);
+
+        let parsed = KnownAnswers::parse(&text).unwrap();
+        assert!(matches!(parsed.check(1, 1, &Answer::Int(1374)), Check::Match));
+        assert!(matches!(parsed.check(1, 1, &Answer::Int(1375)), Check::Mismatch { expected: "1374" }));
+        assert!(matches!(parsed.check(1, 2, &Answer::Int(1374)), Check::Missing));
+        assert!(matches!(parsed.check(13, 2, &Answer::Grid("* *\n *".to_string())), Check::Match));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(KnownAnswers::parse("# comment\n\n3 1 42\n").is_ok());
+        assert!(KnownAnswers::parse("3 1\n").is_err());
+        assert!(KnownAnswers::parse("x 1 42\n").is_err());
+        assert!(KnownAnswers::parse("3 3 42\n").is_err());
+    }
+}
@@ -0,0 +1,292 @@
+use aoc21::answers::ANSWERS_FILE_NAME;
+use aoc21::{Parts, NUM_DAYS};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "Usage: aoc21 [--day <days>] [--part <1|2>] [--input <file>]
+             [--jobs <n>] [--time] [--repeat <n>] [--csv <file>]
+             [--format <text|json>] [--verify | --bless] [<root_dir> | -]
+
+    <root_dir>        Directory containing the dayN_input.txt files.
+    -                 Read the input from stdin instead. Requires exactly one
+                      selected day.
+    --day <days>      Comma separated days or ranges to run, e.g. 5,12-14.
+                      Defaults to all days.
+    --part <1|2>      Only solve the given part. Defaults to both.
+    --input <file>    Read the input from <file> instead of <root_dir>.
+                      Requires exactly one selected day.
+    --jobs <n>        Run up to <n> days at the same time, 0 uses one thread
+                      per CPU. Defaults to 1.
+    --time            Print a table with the time taken by each phase.
+    --repeat <n>      Run each day <n> times and report the median times.
+                      Defaults to 1.
+    --csv <file>      Also write the timings as CSV to <file>.
+    --format <fmt>    Print the results as text, or as json with one object
+                      per line for each part of each day. Defaults to text.
+    --verify          Compare the answers to <root_dir>/answers.txt and exit
+                      with an error if any of them changed.
+    --bless           Record the answers in <root_dir>/answers.txt.";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    /// One JSON object per line and part, with day, part, answer, duration_ms and error fields.
+    Json,
+}
+
+pub struct Options {
+    pub days: Vec<u32>,
+    pub parts: Parts,
+    pub input: Option<PathBuf>,
+    pub root_dir: Option<PathBuf>,
+    pub stdin: bool,
+    pub jobs: usize,
+    pub time: bool,
+    pub repeat: usize,
+    pub csv: Option<PathBuf>,
+    pub format: Format,
+    pub verify: bool,
+    pub bless: bool,
+}
+
+impl Options {
+    /// Returns the input file the given day should read.
+    pub fn input_path(&self, day: u32) -> PathBuf {
+        match (&self.input, &self.root_dir) {
+            (Some(input), _) => input.clone(),
+            (None, Some(root_dir)) => root_dir.join(format!("day{}_input.txt", day)),
+            (None, None) => unreachable!("Only called when not reading from stdin"),
+        }
+    }
+
+    /// Returns the file with the recorded answers for the inputs in the root dir.
+    pub fn answers_path(&self) -> Option<PathBuf> {
+        self.root_dir.as_ref().map(|root_dir| root_dir.join(ANSWERS_FILE_NAME))
+    }
+
+    /// Whether phase timings should be collected and reported.
+    pub fn timing_enabled(&self) -> bool {
+        self.time || self.csv.is_some()
+    }
+}
+
+fn parse_day(tok: &str) -> Result<u32, String> {
+    match tok.trim().parse::<u32>() {
+        Ok(day) if (1..=NUM_DAYS).contains(&day) => Ok(day),
+        _ => Err(format!("Unknown day '{}', expected 1 to {}", tok, NUM_DAYS)),
+    }
+}
+
+/// Parses a day list like "5,12-14" into a sorted list of unique days.
+pub fn parse_day_list(list: &str) -> Result<Vec<u32>, String> {
+    let mut days = Vec::new();
+
+    for item in list.split(',') {
+        match item.split_once('-') {
+            Some((first, last)) => {
+                let first = parse_day(first)?;
+                let last = parse_day(last)?;
+                if first > last {
+                    return Err(format!("Invalid day range '{}'", item));
+                }
+                days.extend(first..=last);
+            },
+            None => days.push(parse_day(item)?),
+        }
+    }
+
+    days.sort_unstable();
+    days.dedup();
+    Ok(days)
+}
+
+fn parse_part(tok: &str) -> Result<Parts, String> {
+    match tok {
+        "1" => Ok(Parts { one: true, two: false }),
+        "2" => Ok(Parts { one: false, two: true }),
+        _ => Err(format!("Unknown part '{}', expected 1 or 2", tok)),
+    }
+}
+
+fn parse_repeat(tok: &str) -> Result<usize, String> {
+    match tok.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("Invalid repeat count '{}', expected a positive number", tok)),
+    }
+}
+
+fn parse_format(tok: &str) -> Result<Format, String> {
+    match tok {
+        "text" => Ok(Format::Text),
+        "json" => Ok(Format::Json),
+        _ => Err(format!("Unknown format '{}', expected text or json", tok)),
+    }
+}
+
+fn parse_jobs(tok: &str) -> Result<usize, String> {
+    tok.parse::<usize>().map_err(|_| format!("Invalid job count '{}', expected a number", tok))
+}
+
+/// Parses the program arguments, excluding the program name itself.
+pub fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        days: (1..=NUM_DAYS).collect(),
+        parts: Parts::BOTH,
+        input: None,
+        root_dir: None,
+        stdin: false,
+        jobs: 1,
+        time: false,
+        repeat: 1,
+        csv: None,
+        format: Format::Text,
+        verify: false,
+        bless: false,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value_for = |flag: &str| {
+            args.next().ok_or(format!("Missing value for {}", flag))
+        };
+
+        match arg.as_str() {
+            "--day" => options.days = parse_day_list(value_for("--day")?)?,
+            "--part" => options.parts = parse_part(value_for("--part")?)?,
+            "--input" => options.input = Some(PathBuf::from(value_for("--input")?)),
+            "--jobs" => options.jobs = parse_jobs(value_for("--jobs")?)?,
+            "--time" => options.time = true,
+            "--repeat" => options.repeat = parse_repeat(value_for("--repeat")?)?,
+            "--csv" => options.csv = Some(PathBuf::from(value_for("--csv")?)),
+            "--format" => options.format = parse_format(value_for("--format")?)?,
+            "--verify" => options.verify = true,
+            "--bless" => options.bless = true,
+            flag if flag.starts_with("--") => {
+                return Err(format!("Unknown option '{}'", flag));
+            },
+            "-" if options.root_dir.is_none() && !options.stdin => options.stdin = true,
+            path => {
+                if options.root_dir.is_some() || options.stdin {
+                    return Err(format!("Unexpected argument '{}'", path));
+                }
+                options.root_dir = Some(PathBuf::from(path));
+            },
+        }
+    }
+
+    if options.input.is_some() && options.days.len() != 1 {
+        return Err("--input requires exactly one day to be selected".to_string());
+    }
+
+    if options.stdin && options.days.len() != 1 {
+        return Err("Reading from stdin requires exactly one day to be selected".to_string());
+    }
+
+    if options.stdin && options.input.is_some() {
+        return Err("--input can't be used when reading from stdin".to_string());
+    }
+
+    if options.verify && options.bless {
+        return Err("--verify and --bless can't be used together".to_string());
+    }
+
+    if (options.verify || options.bless) && (options.input.is_some() || options.stdin) {
+        return Err("--verify and --bless only work with the inputs in <root_dir>".to_string());
+    }
+
+    if options.input.is_none() && options.root_dir.is_none() && !options.stdin {
+        return Err("No input directory provided".to_string());
+    }
+
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn day_list() {
+        assert_eq!(vec![5, 12, 13, 14], parse_day_list("5,12-14").unwrap());
+        assert_eq!(vec![1, 2, 3], parse_day_list("3,1-2,2").unwrap());
+        assert_eq!(vec![25], parse_day_list("25").unwrap());
+    }
+
+    #[test]
+    fn day_list_errors() {
+        assert!(parse_day_list("0").is_err());
+        assert!(parse_day_list("26").is_err());
+        assert!(parse_day_list("14-12").is_err());
+        assert!(parse_day_list("5,").is_err());
+        assert!(parse_day_list("x").is_err());
+    }
+
+    #[test]
+    fn full_args() {
+        let options = parse_args(&args("--day 7 --part 2 --input foo.txt")).unwrap();
+        assert_eq!(vec![7], options.days);
+        assert!(!options.parts.one && options.parts.two);
+        assert_eq!(PathBuf::from("foo.txt"), options.input_path(7));
+
+        let options = parse_args(&args("inputs --day 3-4")).unwrap();
+        assert_eq!(PathBuf::from("inputs/day4_input.txt"), options.input_path(4));
+        assert!(!options.timing_enabled());
+        assert_eq!(1, options.repeat);
+        assert_eq!(1, options.jobs);
+
+        let options = parse_args(&args("inputs --jobs 0")).unwrap();
+        assert_eq!(0, options.jobs);
+
+        let options = parse_args(&args("--day 7 -")).unwrap();
+        assert!(options.stdin);
+        assert_eq!(None, options.root_dir);
+    }
+
+    #[test]
+    fn timing_args() {
+        let options = parse_args(&args("inputs --time --repeat 5")).unwrap();
+        assert!(options.timing_enabled());
+        assert_eq!(5, options.repeat);
+
+        let options = parse_args(&args("inputs --csv times.csv")).unwrap();
+        assert!(options.timing_enabled());
+        assert_eq!(Some(PathBuf::from("times.csv")), options.csv);
+    }
+
+    #[test]
+    fn format_args() {
+        assert_eq!(Format::Text, parse_args(&args("inputs")).unwrap().format);
+        assert_eq!(Format::Json, parse_args(&args("inputs --format json")).unwrap().format);
+        assert!(parse_args(&args("inputs --format yaml")).is_err());
+    }
+
+    #[test]
+    fn answer_args() {
+        let options = parse_args(&args("inputs --verify")).unwrap();
+        assert!(options.verify && !options.bless);
+        assert_eq!(Some(PathBuf::from("inputs/answers.txt")), options.answers_path());
+
+        assert!(parse_args(&args("inputs --verify --bless")).is_err());
+        assert!(parse_args(&args("inputs --day 3 --input foo.txt --bless")).is_err());
+    }
+
+    #[test]
+    fn usage_errors() {
+        assert!(parse_args(&args("")).is_err());
+        assert!(parse_args(&args("--input foo.txt")).is_err());
+        assert!(parse_args(&args("inputs --part 3")).is_err());
+        assert!(parse_args(&args("inputs --day")).is_err());
+        assert!(parse_args(&args("inputs --verbose")).is_err());
+        assert!(parse_args(&args("inputs other")).is_err());
+        assert!(parse_args(&args("inputs --repeat 0")).is_err());
+        assert!(parse_args(&args("inputs --csv")).is_err());
+        assert!(parse_args(&args("inputs --jobs -1")).is_err());
+        assert!(parse_args(&args("-")).is_err());
+        assert!(parse_args(&args("--day 7 - -")).is_err());
+        assert!(parse_args(&args("--day 7 - inputs")).is_err());
+        assert!(parse_args(&args("--day 7 --input foo.txt -")).is_err());
+    }
+}
@@ -1,211 +1,188 @@
+pub mod bitset;
+pub mod bitstream;
+pub mod float;
+pub mod geom;
+pub mod graph;
+pub mod grid;
+pub mod memo;
+pub mod ocr;
+pub mod rotation;
+pub mod scanner;
+pub mod search;
+pub mod sparse_grid;
+pub mod vec;
+
+pub use graph::Graph;
+pub use grid::Grid;
+pub use scanner::Scanner;
+pub use sparse_grid::SparseGrid;
+
+use super::error::{AocError, Result};
 use std::fs;
+use std::hash::Hasher;
+use std::io::BufRead;
 use std::path::Path;
 
-/// We're going to assume the asked for file contains no multi-byte characters.
-pub fn read_input_bytes(file_path: &Path) -> Vec<u8> {
-    match fs::read(&file_path) {
-        Err(why) => panic!("Failed to open input file {}: {}",
-                           file_path.to_str().unwrap(), why),
-        Ok(bytes) => bytes
-    }
-}
-
-/// Returns a triple of row-major grid of values, width and height of the grid.
-pub fn parse_grid(bytes: &Vec<u8>) -> (Vec<u8>, usize, usize) {    
-    let width = bytes.iter()
-        .position(|c| c.is_ascii_whitespace()).unwrap();
-
-    let grid = bytes.iter()
-        .filter(|c| !c.is_ascii_whitespace())
-        .map(|c| c - ('0' as u8))
-        .collect::<Vec<_>>();
-
-    let height = grid.len() / width;
-
-    (grid, width, height)
+/// A multiplicative hasher for the positions and states the solvers put in hash maps, which
+/// is a lot faster than the default SipHash. We don't need its protection against crafted keys.
+#[derive(Default)]
+pub(crate) struct FastHasher {
+    hash: u64,
 }
 
-pub fn build_u32(digits: &Vec<u8>) -> u32 {
-    let mut accumulator: u32  = 0;
-    let mut position = 1;
-
-    for digit in digits.iter().rev() {
-        accumulator += (*digit as u32) * position;
-        position *= 10;
-    }
-
-    accumulator
-}
-
-/// Returns a pair of the parsed number and the new cursor. The cursor
-/// will sit at the first position past the parsed number.
-pub fn parse_num(bytes: &Vec<u8>, token_buf: &mut Vec<u8>, mut cursor: usize) -> (Option<i32>, usize) {
-    let mut is_neg = false; 
-    while cursor < bytes.len() {
-        match bytes[cursor] as char {
-            '0'..='9' => {
-                let digit_val = bytes[cursor] - ('0' as u8);
-                token_buf.push(digit_val);    
-            },
-            '-' => is_neg = true,
-            _ => break,
+impl Hasher for FastHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.write_u64(*byte as u64);
         }
+    }
 
-        cursor += 1;
+    fn write_u32(&mut self, value: u32) {
+        self.write_u64(value as u64);
     }
 
-    if token_buf.len() > 0 {
-        let mut num = build_u32(token_buf) as i32;
-        if is_neg {
-            num *= -1;
-        }
+    fn write_i64(&mut self, value: i64) {
+        self.write_u64(value as u64);
+    }
 
-        token_buf.clear();
-        (Some(num), cursor)
-    } else {
-        (None, cursor)
+    fn write_u64(&mut self, value: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ value).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
     }
-}
 
-pub fn read_list_of_csv_i32s(bytes: &Vec<u8>) -> Vec<i32> {
-    let mut cursor = 0;    
-    let mut values = Vec::new();
-    let mut token_buf = Vec::new();
-    token_buf.reserve(32);
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
 
-    while cursor < bytes.len() {
-        match parse_num(&bytes, &mut token_buf, cursor) {
-            (Some(num), new_cursor) => {
-                values.push(num);
-                cursor = new_cursor;
-            },
-            (None, _) => {
-                panic!("We should have a number at each scan, but failed at cursor pos {}", cursor);
-            } 
-        }
-        cursor += 1;
+    fn finish(&self) -> u64 {
+        self.hash
     }
+}
 
-    values
-    
+/// We're going to assume the asked for file contains no multi-byte characters.
+pub fn read_input_bytes(file_path: &Path) -> Result<Vec<u8>> {
+    fs::read(file_path).map_err(|source| AocError::Io { path: Some(file_path.to_path_buf()), source })
 }
 
-pub fn read_list_of_csv_u32s(bytes: &Vec<u8>) -> Vec<u32> {
-    read_list_of_csv_i32s(bytes).into_iter().map(|i| i as u32).collect()
+/// Reads all of the input from a stream, like stdin.
+pub fn read_input_string(mut reader: impl BufRead) -> Result<String> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input).map_err(|source| AocError::Io { path: None, source })?;
+    Ok(input)
 }
 
+/// The integer types the parsing helpers can produce.
+pub trait Integer: Copy {
+    const SIGNED: bool;
 
-pub fn get_grid_idx(x: usize, y: usize, width: usize) -> usize {
-    x + (y * width) 
+    /// Builds a number from its decimal digit values, most significant first.
+    /// Returns None if it doesn't fit.
+    fn from_digits(negative: bool, digits: impl IntoIterator<Item = u8>) -> Option<Self>;
 }
 
-/// Returns the (x, y) coordinates represented by the idx for a grid
-/// of size (width, height). The grid is assumed to be stored row-major.
-pub fn get_grid_xy(idx: usize, width: usize, height: usize) -> (usize, usize) {
-    let y = idx / width;
-    let x = idx - (y * width);
-    (x, y)
+macro_rules! impl_integer {
+    ($($ty:ty),*) => {$(
+        impl Integer for $ty {
+            const SIGNED: bool = <$ty>::MIN != 0;
+
+            fn from_digits(negative: bool, digits: impl IntoIterator<Item = u8>) -> Option<$ty> {
+                // Negative numbers are built downwards, so MIN fits even though -MIN doesn't.
+                digits.into_iter().try_fold(0 as $ty, |acc, digit| {
+                    let acc = acc.checked_mul(10)?;
+                    if negative { acc.checked_sub(digit as $ty) } else { acc.checked_add(digit as $ty) }
+                })
+            }
+        }
+    )*};
 }
 
-/// Generates valid taps for a cross filter at a position (at idx) for a grid of size 
-/// (width, height). A tap is invalid if it falls outside the grid (edges and corners).
-/// Does not include self as a tap.
-pub fn get_cross_taps(idx: usize, width: usize, height: usize) -> [Option<usize>; 4] {
-    let (x, y) = {
-        let (x, y) = get_grid_xy(idx, width, height);
-        (x as i32, y as i32)
-    };
-
-    let mut coords: [Option<usize>; 4] = Default::default();
-    let mut coord_i = 0;
+impl_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
 
-    let potential_taps = [(x + 1, y), (x - 1, y),
-                          (x, y + 1), (x, y - 1)];
-
-    for tap in potential_taps {
-        if tap.0 < 0 || tap.0 >= width as i32 {
-            continue;
-        }
-    
-        if tap.1 < 0 || tap.1 >= height as i32 {
-            continue;
-        }
-
-        let tap = tap.0 as usize + (tap.1 as usize * width);
-        coords[coord_i] = Some(tap);
-        coord_i += 1;
-    }
-    
-    coords
+/// Builds a non-negative number from digit values (not ASCII), or None if it overflows.
+pub fn build_num<T: Integer>(digits: &[u8]) -> Option<T> {
+    T::from_digits(false, digits.iter().copied())
 }
 
-/// Generates valid taps for a box filter at a position (at idx) for a grid of size 
-/// (width, height). A tap is invalid if it falls outside the grid (edges and corners).
-pub fn get_box_taps(idx: usize, width: usize, height: usize) -> [Option<usize>; 9] {
-    let (x, y) = {
-        let (x, y) = get_grid_xy(idx, width, height);
-        (x as i32, y as i32)
-    };
+/// Parses the number at the cursor, which may start with a minus sign for signed types.
+/// Returns the number and the cursor just past its last digit.
+pub fn parse_int<T: Integer>(bytes: &[u8], cursor: usize) -> Result<(T, usize)> {
+    let negative = bytes.get(cursor) == Some(&b'-');
+    let start = if negative { cursor + 1 } else { cursor };
+    let end = bytes[start.min(bytes.len())..].iter()
+        .position(|b| !b.is_ascii_digit())
+        .map_or(bytes.len(), |len| start + len);
+
+    if start >= end {
+        return Err(AocError::parse_at(bytes, cursor, "Expected a number"));
+    }
+    if negative && !T::SIGNED {
+        return Err(AocError::parse_at(bytes, cursor, "Expected only positive numbers"));
+    }
 
-    let mut coords: [Option<usize>; 9] = Default::default();
-    let mut coord_i = 0;
+    let value = T::from_digits(negative, bytes[start..end].iter().map(|b| b - b'0'))
+        .ok_or_else(|| {
+            AocError::parse_at(bytes, cursor, format!("Number doesn't fit in {}", std::any::type_name::<T>()))
+        })?;
+    Ok((value, end))
+}
 
-    for d_x in [-1,0,1] {
-        let next_x = x + d_x;
-        if next_x < 0 || next_x >= width as i32 {
-            continue;
+/// Reads numbers separated by commas and optional whitespace, like "3,4, -5\n".
+pub fn read_list_of_csv<T: Integer>(bytes: &[u8]) -> Result<Vec<T>> {
+    let skip_ws = |mut cursor: usize| {
+        while cursor < bytes.len() && bytes[cursor].is_ascii_whitespace() {
+            cursor += 1;
         }
-        
-        for d_y in [-1,0,1] {
-            let next_y = y + d_y;
-            if next_y < 0 || next_y >= height as i32 {
-                continue;
-            }
+        cursor
+    };
 
-            let tap = next_x as usize + (next_y as usize * width);
-            coords[coord_i] = Some(tap);
-            coord_i += 1;
+    let mut values = Vec::new();
+    let mut cursor = skip_ws(0);
+    while cursor < bytes.len() {
+        let (value, end) = parse_int(bytes, cursor)?;
+        values.push(value);
+
+        cursor = skip_ws(end);
+        match bytes.get(cursor) {
+            Some(b',') => cursor = skip_ws(cursor + 1),
+            Some(_) => return Err(AocError::parse_at(bytes, cursor, "Expected ','")),
+            None => {},
         }
     }
-    
-    coords
-}
-
-// https://floating-point-gui.de/errors/comparison/
-pub fn f32_near_equal(a: f32, b: f32) -> bool {
-    let pretty_small_flt = 1.0e-8;
-    let abs_a = a.abs();
-    let abs_b = b.abs();
-    let diff = (a - b).abs();
-
-    if a == b { // shortcut, handles infinities
-        return true;
-    } else if (a == 0.0) || (b == 0.0) || ((abs_a + abs_b) < f32::MIN) {
-        // a or b is zero or both are extremely close to it
-        // relative error is less meaningful here
-        return diff < (pretty_small_flt * f32::MIN);
-    } else { // use relative error
-        return diff / f32::min(abs_a + abs_b, f32::MAX) < pretty_small_flt;
-    }
-}
 
-pub fn is_newline(c: char) -> bool {
-    (c == '\n') || (c == '\r')
+    Ok(values)
 }
 
 #[cfg(test)] 
 mod tests {
-    use super::f32_near_equal;
+    use super::*;
+
+    fn error_pos<T>(result: Result<T>) -> (usize, usize) {
+        match result {
+            Err(AocError::Parse { line, column, .. }) => (line, column),
+            _ => panic!("Expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn checked_parsing() {
+        assert_eq!((i64::MIN, 20), parse_int::<i64>(b"-9223372036854775808", 0).unwrap());
+        assert_eq!((u64::MAX, 21), parse_int::<u64>(b" 18446744073709551615x", 1).unwrap());
+        assert_eq!(i128::MAX, parse_int::<i128>(i128::MAX.to_string().as_bytes(), 0).unwrap().0);
+        assert_eq!(Some(4_294_967_295u32), build_num(&[4, 2, 9, 4, 9, 6, 7, 2, 9, 5]));
+        assert_eq!(None, build_num::<u32>(&[4, 2, 9, 4, 9, 6, 7, 2, 9, 6]));
+
+        assert_eq!((1, 3), error_pos(parse_int::<i64>(b"x 9223372036854775808", 2)));
+        assert_eq!((1, 1), error_pos(parse_int::<u64>(b"-5", 0)));
+        assert_eq!((1, 1), error_pos(parse_int::<i32>(b"-", 0)));
+        assert_eq!((1, 1), error_pos(parse_int::<i128>(b"", 0)));
+    }
 
     #[test]
-    fn flt_test() {
-        assert!(f32_near_equal(0.0, 0.0));
-        assert!(f32_near_equal(0.1, 0.1));
-        assert!(f32_near_equal(0.001240, 0.001240));
-        assert!(!f32_near_equal(0.001240, 0.0012438));
-        assert!(!f32_near_equal(3.5, 5.3));
-        assert!(f32_near_equal(1317.00452, 1317.00452));
-        assert!(!f32_near_equal(1317.00452, 1317.00422));
-        assert!(!f32_near_equal(1317.00452, 1317.10022));
+    fn csv_lists() {
+        assert_eq!(vec![3, 4, -5], read_list_of_csv::<i8>(b"3,4, -5\n").unwrap());
+        assert_eq!(vec![5_000_000_000u64], read_list_of_csv::<u64>(b"5000000000").unwrap());
+        assert!(read_list_of_csv::<u32>(b"").unwrap().is_empty());
+        assert_eq!((2, 3), error_pos(read_list_of_csv::<u8>(b"1,\n2,256")));
+        assert_eq!((1, 3), error_pos(read_list_of_csv::<u8>(b"1 2")));
+        assert_eq!((1, 3), error_pos(read_list_of_csv::<u32>(b"1,-2")));
     }
 }
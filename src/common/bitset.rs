@@ -0,0 +1,459 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+fn word_count(bits: usize) -> usize {
+    bits.div_ceil(WORD_BITS)
+}
+
+/// The number of set bits below `i`.
+fn rank(words: &[u64], i: usize) -> usize {
+    let (full, rest) = (i / WORD_BITS, i % WORD_BITS);
+    let below = words.iter().take(full).map(|w| w.count_ones() as usize).sum::<usize>();
+    match words.get(full) {
+        Some(word) if rest > 0 => below + (word & ((1 << rest) - 1)).count_ones() as usize,
+        _ => below,
+    }
+}
+
+/// The position of the `k`th set bit, counting from 0.
+fn select(words: &[u64], mut k: usize) -> Option<usize> {
+    for (idx, word) in words.iter().enumerate() {
+        let ones = word.count_ones() as usize;
+        if k < ones {
+            let mut word = *word;
+            for _ in 0..k {
+                word &= word - 1;
+            }
+            return Some(idx * WORD_BITS + word.trailing_zeros() as usize);
+        }
+        k -= ones;
+    }
+    None
+}
+
+/// Iterates over the set bits in increasing order.
+#[derive(Clone)]
+pub struct Ones<'a> {
+    words: &'a [u64],
+    base: usize,
+    current: u64,
+}
+
+impl<'a> Ones<'a> {
+    fn new(words: &'a [u64]) -> Ones<'a> {
+        Ones { words, base: 0, current: 0 }
+    }
+}
+
+impl Iterator for Ones<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        // `base` is where the word after `current` starts.
+        while self.current == 0 {
+            let (first, rest) = self.words.split_first()?;
+            self.current = *first;
+            self.words = rest;
+            self.base += WORD_BITS;
+        }
+
+        let bit = self.current.trailing_zeros() as usize;
+        self.current &= self.current - 1;
+        Some(self.base - WORD_BITS + bit)
+    }
+}
+
+/// A set of small integers below `64 * WORDS`, stored inline. It's `Copy`, so the operators
+/// take it by value.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitSet<const WORDS: usize = 1> {
+    words: [u64; WORDS],
+}
+
+impl<const WORDS: usize> Default for BitSet<WORDS> {
+    fn default() -> BitSet<WORDS> {
+        BitSet::new()
+    }
+}
+
+impl<const WORDS: usize> BitSet<WORDS> {
+    /// The number of bits the set can hold.
+    pub const CAPACITY: usize = WORDS * WORD_BITS;
+
+    pub const fn new() -> BitSet<WORDS> {
+        BitSet { words: [0; WORDS] }
+    }
+
+    pub const fn from_words(words: [u64; WORDS]) -> BitSet<WORDS> {
+        BitSet { words }
+    }
+
+    /// The bits as words, bit 0 being the lowest bit of the first word.
+    pub fn words(&self) -> &[u64; WORDS] {
+        &self.words
+    }
+
+    /// Adds `i`, returning whether it wasn't in the set yet. Panics if `i` is out of range.
+    pub fn insert(&mut self, i: usize) -> bool {
+        assert!(i < Self::CAPACITY, "Bit {} out of range for a set of {} bits", i, Self::CAPACITY);
+        let added = !self.contains(i);
+        self.words[i / WORD_BITS] |= 1 << (i % WORD_BITS);
+        added
+    }
+
+    /// Removes `i`, returning whether it was in the set.
+    pub fn remove(&mut self, i: usize) -> bool {
+        let removed = self.contains(i);
+        if removed {
+            self.words[i / WORD_BITS] &= !(1 << (i % WORD_BITS));
+        }
+        removed
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        i < Self::CAPACITY && self.words[i / WORD_BITS] & (1 << (i % WORD_BITS)) != 0
+    }
+
+    /// The number of bits set.
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.words = [0; WORDS];
+    }
+
+    pub fn iter(&self) -> Ones<'_> {
+        Ones::new(&self.words)
+    }
+
+    /// The number of set bits below `i`.
+    pub fn rank(&self, i: usize) -> usize {
+        rank(&self.words, i)
+    }
+
+    /// The `k`th set bit, counting from 0.
+    pub fn select(&self, k: usize) -> Option<usize> {
+        select(&self.words, k)
+    }
+
+    pub fn is_subset(&self, other: &BitSet<WORDS>) -> bool {
+        self.words.iter().zip(&other.words).all(|(a, b)| a & !b == 0)
+    }
+
+    pub fn is_superset(&self, other: &BitSet<WORDS>) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &BitSet<WORDS>) -> bool {
+        self.words.iter().zip(&other.words).all(|(a, b)| a & b == 0)
+    }
+}
+
+impl<const WORDS: usize> FromIterator<usize> for BitSet<WORDS> {
+    fn from_iter<I: IntoIterator<Item = usize>>(bits: I) -> BitSet<WORDS> {
+        let mut set = BitSet::new();
+        for i in bits {
+            set.insert(i);
+        }
+        set
+    }
+}
+
+impl<'a, const WORDS: usize> IntoIterator for &'a BitSet<WORDS> {
+    type Item = usize;
+    type IntoIter = Ones<'a>;
+
+    fn into_iter(self) -> Ones<'a> {
+        self.iter()
+    }
+}
+
+/// The complement within the capacity of the set.
+impl<const WORDS: usize> Not for BitSet<WORDS> {
+    type Output = BitSet<WORDS>;
+
+    fn not(self) -> BitSet<WORDS> {
+        BitSet { words: self.words.map(|w| !w) }
+    }
+}
+
+/// A set of integers that grows to fit whatever gets inserted.
+#[derive(Clone, Default)]
+pub struct DynBitSet {
+    words: Vec<u64>,
+    bit_len: usize,
+}
+
+impl DynBitSet {
+    pub fn new() -> DynBitSet {
+        DynBitSet::default()
+    }
+
+    /// An empty set that already spans `bit_len` bits.
+    pub fn with_len(bit_len: usize) -> DynBitSet {
+        DynBitSet { words: vec![0; word_count(bit_len)], bit_len }
+    }
+
+    /// The number of bits the set spans, set or not. `!` complements within this range.
+    pub fn bit_len(&self) -> usize {
+        self.bit_len
+    }
+
+    /// The bits as words, bit 0 being the lowest bit of the first word.
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    fn grow(&mut self, bit_len: usize) {
+        if bit_len > self.bit_len {
+            self.bit_len = bit_len;
+            self.words.resize(word_count(bit_len), 0);
+        }
+    }
+
+    /// Adds `i`, growing the set if needed. Returns whether it wasn't in the set yet.
+    pub fn insert(&mut self, i: usize) -> bool {
+        self.grow(i + 1);
+        let added = !self.contains(i);
+        self.words[i / WORD_BITS] |= 1 << (i % WORD_BITS);
+        added
+    }
+
+    /// Removes `i`, returning whether it was in the set.
+    pub fn remove(&mut self, i: usize) -> bool {
+        let removed = self.contains(i);
+        if removed {
+            self.words[i / WORD_BITS] &= !(1 << (i % WORD_BITS));
+        }
+        removed
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        self.words.get(i / WORD_BITS).is_some_and(|w| w & (1 << (i % WORD_BITS)) != 0)
+    }
+
+    /// The number of bits set.
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// Unsets all bits, keeping the length.
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub fn iter(&self) -> Ones<'_> {
+        Ones::new(&self.words)
+    }
+
+    /// The number of set bits below `i`.
+    pub fn rank(&self, i: usize) -> usize {
+        rank(&self.words, i)
+    }
+
+    /// The `k`th set bit, counting from 0.
+    pub fn select(&self, k: usize) -> Option<usize> {
+        select(&self.words, k)
+    }
+
+    pub fn is_subset(&self, other: &DynBitSet) -> bool {
+        self.words.iter().enumerate().all(|(idx, a)| a & !other.words.get(idx).unwrap_or(&0) == 0)
+    }
+
+    pub fn is_superset(&self, other: &DynBitSet) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &DynBitSet) -> bool {
+        self.words.iter().zip(&other.words).all(|(a, b)| a & b == 0)
+    }
+
+    /// Combines the words of both sets, treating missing words as empty.
+    fn combine(&self, other: &DynBitSet, op: impl Fn(u64, u64) -> u64) -> DynBitSet {
+        let mut out = DynBitSet::with_len(self.bit_len.max(other.bit_len));
+        for (idx, word) in out.words.iter_mut().enumerate() {
+            *word = op(*self.words.get(idx).unwrap_or(&0), *other.words.get(idx).unwrap_or(&0));
+        }
+        out
+    }
+}
+
+/// Sets are equal when the same bits are set, however far they happen to span.
+impl PartialEq for DynBitSet {
+    fn eq(&self, other: &DynBitSet) -> bool {
+        let len = self.words.len().max(other.words.len());
+        (0..len).all(|idx| self.words.get(idx).unwrap_or(&0) == other.words.get(idx).unwrap_or(&0))
+    }
+}
+
+impl Eq for DynBitSet {}
+
+/// Hashes like it compares, leaving out empty words at the end.
+impl Hash for DynBitSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let len = self.words.iter().rposition(|w| *w != 0).map_or(0, |idx| idx + 1);
+        self.words[..len].hash(state);
+    }
+}
+
+impl FromIterator<usize> for DynBitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(bits: I) -> DynBitSet {
+        let mut set = DynBitSet::new();
+        for i in bits {
+            set.insert(i);
+        }
+        set
+    }
+}
+
+impl<'a> IntoIterator for &'a DynBitSet {
+    type Item = usize;
+    type IntoIter = Ones<'a>;
+
+    fn into_iter(self) -> Ones<'a> {
+        self.iter()
+    }
+}
+
+/// The complement within `bit_len`.
+impl Not for &DynBitSet {
+    type Output = DynBitSet;
+
+    fn not(self) -> DynBitSet {
+        let mut out = DynBitSet { words: self.words.iter().map(|w| !w).collect(), bit_len: self.bit_len };
+        if let Some(last) = out.words.last_mut() {
+            let rest = self.bit_len % WORD_BITS;
+            if rest > 0 {
+                *last &= (1 << rest) - 1;
+            }
+        }
+        out
+    }
+}
+
+/// Implements a binary operator and its assigning version for both kinds of sets.
+macro_rules! impl_op {
+    ($op:ident, $fn:ident, $assign_op:ident, $assign_fn:ident, $word_op:tt) => {
+        impl<const WORDS: usize> $op for BitSet<WORDS> {
+            type Output = BitSet<WORDS>;
+            fn $fn(mut self, other: BitSet<WORDS>) -> BitSet<WORDS> {
+                self.$assign_fn(other);
+                self
+            }
+        }
+
+        impl<const WORDS: usize> $assign_op for BitSet<WORDS> {
+            fn $assign_fn(&mut self, other: BitSet<WORDS>) {
+                for (a, b) in self.words.iter_mut().zip(other.words) {
+                    *a = *a $word_op b;
+                }
+            }
+        }
+
+        impl $op for &DynBitSet {
+            type Output = DynBitSet;
+            fn $fn(self, other: &DynBitSet) -> DynBitSet {
+                self.combine(other, |a, b| a $word_op b)
+            }
+        }
+
+        impl $assign_op<&DynBitSet> for DynBitSet {
+            fn $assign_fn(&mut self, other: &DynBitSet) {
+                *self = (&*self).$fn(other);
+            }
+        }
+    };
+}
+
+impl_op!(BitOr, bitor, BitOrAssign, bitor_assign, |);
+impl_op!(BitAnd, bitand, BitAndAssign, bitand_assign, &);
+impl_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, ^);
+
+impl<const WORDS: usize> fmt::Debug for BitSet<WORDS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl fmt::Debug for DynBitSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed() {
+        let mut a: BitSet<2> = [1, 5, 64, 127].into_iter().collect();
+        assert!(a.insert(70) && !a.insert(70));
+        assert!(a.remove(5) && !a.remove(5));
+        assert_eq!(vec![1, 64, 70, 127], a.iter().collect::<Vec<_>>());
+        assert_eq!((4, false), (a.len(), a.is_empty()));
+        assert!(!a.contains(128) && !a.contains(0));
+
+        let b: BitSet<2> = [1, 2, 64].into_iter().collect();
+        assert_eq!(vec![1, 64], (a & b).iter().collect::<Vec<_>>());
+        assert_eq!(vec![1, 2, 64, 70, 127], (a | b).iter().collect::<Vec<_>>());
+        assert_eq!(vec![2, 70, 127], (a ^ b).iter().collect::<Vec<_>>());
+        assert_eq!(vec![70, 127], (a & !b).iter().collect::<Vec<_>>());
+        assert_eq!(124, (!a).len());
+
+        assert!((a & b).is_subset(&a) && a.is_superset(&(a & b)) && !a.is_subset(&b));
+        assert!((a & !b).is_disjoint(&b));
+        assert_eq!("{1, 64, 70, 127}", format!("{:?}", a));
+    }
+
+    #[test]
+    fn rank_select() {
+        let set: BitSet<3> = [0, 3, 63, 64, 100, 191].into_iter().collect();
+        for (k, i) in set.iter().enumerate() {
+            assert_eq!(Some(i), set.select(k));
+            assert_eq!(k, set.rank(i));
+        }
+        assert_eq!(None, set.select(6));
+        assert_eq!((2, 3, 6), (set.rank(63), set.rank(64), set.rank(192)));
+        assert_eq!(None, BitSet::<1>::new().select(0));
+    }
+
+    #[test]
+    fn dynamic() {
+        let mut a = DynBitSet::new();
+        assert!(a.insert(3) && a.insert(200));
+        assert_eq!((201, 2), (a.bit_len(), a.len()));
+
+        let b: DynBitSet = [3, 4].into_iter().collect();
+        assert_eq!(vec![3, 4, 200], (&a | &b).iter().collect::<Vec<_>>());
+        assert_eq!(vec![4, 200], (&a ^ &b).iter().collect::<Vec<_>>());
+        assert_eq!(b, &(&a | &b) & &b);
+        assert!(!b.is_subset(&a) && (&a & &b).is_subset(&b) && a.is_superset(&(&a & &b)));
+
+        // The complement stays within the bits the set spans.
+        assert_eq!(vec![0, 1, 2], (!&b).iter().collect::<Vec<_>>());
+        assert_eq!(199, (!&a).len());
+        assert_eq!((Some(200), 1), (a.select(1), a.rank(4)));
+
+        a &= &b;
+        assert_eq!(DynBitSet::from_iter([3]), a);
+        a.clear();
+        assert!(a.is_empty() && a == DynBitSet::new());
+
+        // Equal sets hash the same, even when one spans more words.
+        let hashes = std::collections::HashSet::from([DynBitSet::with_len(500), DynBitSet::new()]);
+        assert_eq!(1, hashes.len());
+    }
+}
@@ -0,0 +1,290 @@
+use crate::error::{AocError, Result};
+
+/// Which end of each byte the bits start at. With `MsbFirst` the first bit read is also the
+/// most significant bit of the value, with `LsbFirst` it's the least significant one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitOrder {
+    MsbFirst,
+    LsbFirst,
+}
+
+fn shift_in_byte(order: BitOrder, pos: usize) -> usize {
+    match order {
+        BitOrder::MsbFirst => 7 - pos % 8,
+        BitOrder::LsbFirst => pos % 8,
+    }
+}
+
+/// Decodes pairs of hex digits into bytes, in either case.
+pub fn decode_hex(hex: &[u8]) -> Result<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return Err(AocError::parse_at(hex, hex.len(), "Expected an even number of hex digits"));
+    }
+
+    let digit = |idx: usize| {
+        (hex[idx] as char).to_digit(16).map(|d| d as u8).ok_or_else(|| AocError::parse_at(hex, idx,
+            format!("Unexpected char '{}', expected a hex digit", hex[idx] as char)))
+    };
+    (0..hex.len()).step_by(2).map(|idx| Ok((digit(idx)? << 4) | digit(idx + 1)?)).collect()
+}
+
+/// Encodes the bytes as upper case hex digits.
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// Reads values of up to 64 bits at a time from a byte slice.
+#[derive(Clone)]
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    order: BitOrder,
+    pos: usize,
+    bits_per_column: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8], order: BitOrder) -> BitReader<'a> {
+        BitReader { bytes, order, pos: 0, bits_per_column: 8 }
+    }
+
+    /// Sets how many bits each column of the input held, so errors point at the right place.
+    /// That's 4 for bytes decoded from hex, the default is a column per byte.
+    pub fn with_bits_per_column(mut self, bits: usize) -> BitReader<'a> {
+        self.bits_per_column = bits;
+        self
+    }
+
+    /// The position of the next bit to read.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// The total number of bits.
+    pub fn len(&self) -> usize {
+        self.bytes.len() * 8
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.len() - self.pos
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.pos == self.len()
+    }
+
+    /// A parse error pointing at the input column holding the bit.
+    pub fn error_at(&self, bit_pos: usize, message: impl Into<String>) -> AocError {
+        AocError::parse(1, bit_pos / self.bits_per_column + 1, message)
+    }
+
+    /// A parse error at the current position.
+    pub fn error(&self, message: impl Into<String>) -> AocError {
+        self.error_at(self.pos, message)
+    }
+
+    /// Moves to `pos`, which may be the end but not past it.
+    pub fn seek(&mut self, pos: usize) -> Result<()> {
+        if pos > self.len() {
+            return Err(self.error(format!("Can't seek to bit {}, there are only {}", pos, self.len())));
+        }
+        self.pos = pos;
+        Ok(())
+    }
+
+    pub fn skip(&mut self, num_bits: usize) -> Result<()> {
+        self.check(num_bits)?;
+        self.pos += num_bits;
+        Ok(())
+    }
+
+    /// Skips to the next multiple of `align_to` bits, unless already there.
+    pub fn align(&mut self, align_to: usize) -> Result<()> {
+        if align_to == 0 {
+            return Err(self.error("Can't align to a multiple of 0 bits"));
+        }
+        self.seek(self.pos.next_multiple_of(align_to))
+    }
+
+    fn check(&self, num_bits: usize) -> Result<()> {
+        if num_bits > self.remaining() {
+            return Err(self.error(format!("Expected {} more bits, found {}", num_bits, self.remaining())));
+        }
+        Ok(())
+    }
+
+    /// Reads the next `num_bits` bits as a number without moving past them.
+    pub fn peek(&self, num_bits: usize) -> Result<u64> {
+        if num_bits > 64 {
+            return Err(self.error(format!("Can't read {} bits into a u64", num_bits)));
+        }
+        self.check(num_bits)?;
+
+        let mut value = 0;
+        for i in 0..num_bits {
+            let pos = self.pos + i;
+            let bit = ((self.bytes[pos / 8] >> shift_in_byte(self.order, pos)) & 1) as u64;
+            match self.order {
+                BitOrder::MsbFirst => value = (value << 1) | bit,
+                BitOrder::LsbFirst => value |= bit << i,
+            }
+        }
+        Ok(value)
+    }
+
+    pub fn read(&mut self, num_bits: usize) -> Result<u64> {
+        let value = self.peek(num_bits)?;
+        self.pos += num_bits;
+        Ok(value)
+    }
+
+    pub fn read_bit(&mut self) -> Result<bool> {
+        Ok(self.read(1)? == 1)
+    }
+}
+
+/// Writes values of up to 64 bits at a time into a growing byte buffer. The last byte is
+/// padded with zero bits.
+#[derive(Clone)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    order: BitOrder,
+    len: usize,
+}
+
+impl BitWriter {
+    pub fn new(order: BitOrder) -> BitWriter {
+        BitWriter { bytes: Vec::new(), order, len: 0 }
+    }
+
+    /// The number of bits written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn to_hex(&self) -> String {
+        encode_hex(&self.bytes)
+    }
+
+    pub fn write_bit(&mut self, bit: bool) {
+        if self.len.is_multiple_of(8) {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().unwrap() |= 1 << shift_in_byte(self.order, self.len);
+        }
+        self.len += 1;
+    }
+
+    /// Writes the lowest `num_bits` bits of the value. Panics if the value has other bits set,
+    /// or if `num_bits` is over 64.
+    pub fn write(&mut self, value: u64, num_bits: usize) {
+        assert!(num_bits <= 64, "Can't write {} bits from a u64", num_bits);
+        assert!(num_bits == 64 || value >> num_bits == 0, "{} doesn't fit in {} bits", value, num_bits);
+
+        for i in 0..num_bits {
+            let shift = match self.order {
+                BitOrder::MsbFirst => num_bits - 1 - i,
+                BitOrder::LsbFirst => i,
+            };
+            self.write_bit((value >> shift) & 1 == 1);
+        }
+    }
+
+    /// Pads with zero bits up to the next multiple of `align_to` bits. Panics if `align_to` is 0.
+    pub fn align(&mut self, align_to: usize) {
+        assert!(align_to > 0, "Can't align to a multiple of 0 bits");
+        while !self.len.is_multiple_of(align_to) {
+            self.write_bit(false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALUES: [(u64, usize); 7] = [(5, 3), (0, 1), (1, 1), (0x1234, 15), (u64::MAX, 64), (0, 0), (0xABC, 12)];
+
+    #[test]
+    fn round_trip() {
+        for order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            let mut writer = BitWriter::new(order);
+            for (value, bits) in VALUES {
+                writer.write(value, bits);
+            }
+            assert_eq!(96, writer.len());
+
+            let bytes = decode_hex(writer.to_hex().as_bytes()).unwrap();
+            assert_eq!(writer.bytes(), bytes);
+
+            let mut reader = BitReader::new(&bytes, order);
+            for (value, bits) in VALUES {
+                assert_eq!(value, reader.read(bits).unwrap());
+            }
+            assert!(reader.is_at_end());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "multiple of 0 bits")]
+    fn align_writer_to_zero() {
+        BitWriter::new(BitOrder::MsbFirst).align(0);
+    }
+
+    #[test]
+    fn bit_order() {
+        let bytes = [0b1100_0101, 0b0000_0001];
+        let mut msb = BitReader::new(&bytes, BitOrder::MsbFirst);
+        assert_eq!((0b110, 0b00101), (msb.read(3).unwrap(), msb.read(5).unwrap()));
+        assert_eq!(1, msb.read(8).unwrap());
+
+        let mut lsb = BitReader::new(&bytes, BitOrder::LsbFirst);
+        assert_eq!((0b101, 0b11000), (lsb.read(3).unwrap(), lsb.read(5).unwrap()));
+        assert!(lsb.read_bit().unwrap());
+        lsb.align(8).unwrap();
+        assert!(lsb.is_at_end());
+
+        assert_eq!("C501", encode_hex(&bytes));
+        assert_eq!(vec![0xC5, 0x01, 0xAF], decode_hex(b"c501aF").unwrap());
+    }
+
+    #[test]
+    fn errors() {
+        let bytes = decode_hex(b"D2FE28").unwrap();
+        let mut reader = BitReader::new(&bytes, BitOrder::MsbFirst).with_bits_per_column(4);
+        reader.seek(20).unwrap();
+        match reader.read(5) {
+            Err(AocError::Parse { line, column, .. }) => assert_eq!((1, 6), (line, column)),
+            _ => panic!("Expected reading past the end to fail"),
+        }
+
+        // Failed reads leave the position alone.
+        assert_eq!(20, reader.pos());
+        assert_eq!(0b1000, reader.read(4).unwrap());
+        assert!(reader.read(1).is_err() && reader.skip(1).is_err() && reader.seek(25).is_err());
+        assert!(reader.seek(0).is_ok() && reader.peek(65).is_err());
+        assert!(reader.align(0).is_err());
+
+        assert!(decode_hex(b"ABC").is_err());
+        match decode_hex(b"ABCX") {
+            Err(AocError::Parse { line, column, .. }) => assert_eq!((1, 4), (line, column)),
+            _ => panic!("Expected a bad hex digit"),
+        }
+    }
+}
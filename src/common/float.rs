@@ -0,0 +1,178 @@
+// https://floating-point-gui.de/errors/comparison/
+
+/// How far apart two floats may be and still count as equal. They are if they pass any of the
+/// tolerances, so a zero tolerance just doesn't help.
+///
+/// The absolute one is for values near zero, where relative errors blow up. The relative one
+/// scales with the larger value. ULPs count the representable floats between the two, which
+/// also works across the subnormals.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    pub abs: f64,
+    pub rel: f64,
+    pub ulps: u64,
+}
+
+impl Tolerance {
+    /// Only equal values are equal.
+    pub const EXACT: Tolerance = Tolerance { abs: 0.0, rel: 0.0, ulps: 0 };
+
+    /// A few rounding errors' worth, for results of short calculations.
+    pub const DEFAULT: Tolerance = Tolerance::ulps(4);
+
+    pub const fn abs(abs: f64) -> Tolerance {
+        Tolerance { abs, ..Tolerance::EXACT }
+    }
+
+    pub const fn rel(rel: f64) -> Tolerance {
+        Tolerance { rel, ..Tolerance::EXACT }
+    }
+
+    pub const fn ulps(ulps: u64) -> Tolerance {
+        Tolerance { ulps, ..Tolerance::EXACT }
+    }
+
+    pub const fn with_abs(self, abs: f64) -> Tolerance {
+        Tolerance { abs, ..self }
+    }
+
+    pub const fn with_rel(self, rel: f64) -> Tolerance {
+        Tolerance { rel, ..self }
+    }
+
+    pub const fn with_ulps(self, ulps: u64) -> Tolerance {
+        Tolerance { ulps, ..self }
+    }
+}
+
+impl Default for Tolerance {
+    fn default() -> Tolerance {
+        Tolerance::DEFAULT
+    }
+}
+
+pub trait ApproxEq: Copy {
+    /// Whether the values are equal within the tolerance. NaN is never equal to anything,
+    /// and infinities only to themselves.
+    fn approx_eq(self, other: Self, tolerance: Tolerance) -> bool;
+
+    /// The number of representable values from one to the other, with both zeros counting
+    /// as the same value. None if either is NaN.
+    fn ulps_between(self, other: Self) -> Option<u64>;
+}
+
+macro_rules! impl_approx_eq {
+    ($($float:ty: $signed:ty),+) => {$(
+        impl ApproxEq for $float {
+            fn approx_eq(self, other: $float, tolerance: Tolerance) -> bool {
+                if self == other {
+                    return true;
+                }
+                if !self.is_finite() || !other.is_finite() {
+                    return false;
+                }
+
+                if tolerance.ulps > 0 && self.ulps_between(other).is_some_and(|ulps| ulps <= tolerance.ulps) {
+                    return true;
+                }
+
+                let (a, b) = (self as f64, other as f64);
+                let diff = (a - b).abs();
+                diff <= tolerance.abs || diff <= tolerance.rel * a.abs().max(b.abs())
+            }
+
+            fn ulps_between(self, other: $float) -> Option<u64> {
+                if self.is_nan() || other.is_nan() {
+                    return None;
+                }
+
+                // Laid out as sign and magnitude, the bits of the floats of either sign count
+                // up away from zero. Negating the negative ones puts them all in order.
+                let ordered = |x: $float| {
+                    let magnitude = (x.abs().to_bits()) as $signed;
+                    if x.is_sign_negative() { -magnitude } else { magnitude }
+                };
+                Some(ordered(self).abs_diff(ordered(other)) as u64)
+            }
+        }
+    )+};
+}
+
+impl_approx_eq!(f32: i32, f64: i64);
+
+/// Asserts that two floats are equal within a `Tolerance`, `Tolerance::DEFAULT` if left out.
+#[macro_export]
+macro_rules! assert_approx_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_approx_eq!($left, $right, $crate::common::float::Tolerance::DEFAULT)
+    };
+    ($left:expr, $right:expr, $tolerance:expr $(,)?) => {{
+        let (left, right, tolerance) = ($left, $right, $tolerance);
+        if !$crate::common::float::ApproxEq::approx_eq(left, right, tolerance) {
+            panic!("assertion `left approx_eq right` failed\n      left: {:?}\n     right: {:?}\n tolerance: {:?}",
+                   left, right, tolerance);
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::excessive_precision)]
+    fn flt_test() {
+        let strict = Tolerance::ulps(1);
+        assert!(0.0f32.approx_eq(0.0, strict));
+        assert!(0.1f32.approx_eq(0.1, strict));
+        assert!(0.001240f32.approx_eq(0.001240, strict));
+        assert!(!0.001240f32.approx_eq(0.0012438, strict));
+        assert!(!3.5f32.approx_eq(5.3, strict));
+        assert!(1317.00452f32.approx_eq(1317.00452, strict));
+        assert!(!1317.00452f32.approx_eq(1317.00422, strict));
+        assert!(!1317.00452f32.approx_eq(1317.10022, strict));
+    }
+
+    #[test]
+    fn tolerances() {
+        assert!(!(0.1f64 + 0.2).approx_eq(0.3, Tolerance::EXACT));
+        assert_approx_eq!(0.1f64 + 0.2, 0.3);
+        assert_approx_eq!(0.1f32 + 0.2, 0.3);
+
+        assert!(!1e-9f64.approx_eq(0.0, Tolerance::DEFAULT));
+        assert_approx_eq!(1e-9f64, 0.0, Tolerance::abs(1e-8));
+        assert_approx_eq!(1000.0f32, 1000.5, Tolerance::rel(1e-3));
+        assert!(!1000.0f32.approx_eq(1001.5, Tolerance::rel(1e-3)));
+        assert_approx_eq!(1.0f32, 1.1, Tolerance::rel(1e-6).with_abs(0.2));
+
+        assert_eq!(Some(1), 1.0f32.ulps_between(1.0 + f32::EPSILON));
+        assert_eq!(Some(2), 1.0f64.ulps_between(1.0 + 2.0 * f64::EPSILON));
+        assert_eq!(Some(2 * 0x7F7F_FFFF), f32::MIN.ulps_between(f32::MAX));
+    }
+
+    #[test]
+    fn edge_cases() {
+        assert!(!f64::NAN.approx_eq(f64::NAN, Tolerance::abs(f64::INFINITY)));
+        assert!(!f32::NAN.approx_eq(1.0, Tolerance::ulps(u64::MAX)));
+        assert_eq!(None, f32::NAN.ulps_between(0.0));
+
+        assert_approx_eq!(f32::INFINITY, f32::INFINITY, Tolerance::EXACT);
+        assert!(!f64::INFINITY.approx_eq(f64::MAX, Tolerance::ulps(u64::MAX)));
+        assert!(!f64::INFINITY.approx_eq(f64::NEG_INFINITY, Tolerance::abs(f64::INFINITY)));
+
+        // Both zeros are the same value, and the subnormals count up from them.
+        assert_approx_eq!(0.0f32, -0.0, Tolerance::EXACT);
+        let tiny = f32::from_bits(1);
+        assert_eq!(Some(1), tiny.ulps_between(0.0));
+        assert_eq!(Some(2), tiny.ulps_between(-tiny));
+        assert_eq!(Some(1), f64::MIN_POSITIVE.ulps_between(f64::MIN_POSITIVE - f64::from_bits(1)));
+        assert_approx_eq!(tiny, -tiny, Tolerance::ulps(2));
+        assert!(!tiny.approx_eq(-tiny, Tolerance::rel(0.5)));
+    }
+
+    #[test]
+    #[should_panic(expected = "left approx_eq right")]
+    fn failing_assert() {
+        assert_approx_eq!(1.0f64, 1.0001);
+    }
+}
@@ -0,0 +1,314 @@
+/// An axis-aligned box in N dimensions. Both corners are inclusive, so a box from 0 to 0 is a
+/// single unit cell with a volume of 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Aabb<const N: usize> {
+    pub min: [i64; N],
+    pub max: [i64; N],
+}
+
+impl<const N: usize> Aabb<N> {
+    /// Expects `min` to be no larger than `max` on every axis.
+    pub fn new(min: [i64; N], max: [i64; N]) -> Aabb<N> {
+        debug_assert!((0..N).all(|axis| min[axis] <= max[axis]), "Box corners out of order");
+        Aabb { min, max }
+    }
+
+    /// The number of unit cells in the box, or None if that doesn't fit in a u128. A side
+    /// can span all of i64, so even a 3D box can get that big.
+    pub fn volume(&self) -> Option<u128> {
+        (0..N).try_fold(1u128, |volume, axis| {
+            volume.checked_mul(self.max[axis].abs_diff(self.min[axis]) as u128 + 1)
+        })
+    }
+
+    pub fn contains(&self, point: [i64; N]) -> bool {
+        (0..N).all(|axis| (self.min[axis]..=self.max[axis]).contains(&point[axis]))
+    }
+
+    pub fn overlaps(&self, other: &Aabb<N>) -> bool {
+        (0..N).all(|axis| self.max[axis] >= other.min[axis] && self.min[axis] <= other.max[axis])
+    }
+
+    /// The part of the box both have in common, if any. Also what clipping a box to a
+    /// query region comes down to.
+    pub fn intersect(&self, other: &Aabb<N>) -> Option<Aabb<N>> {
+        if !self.overlaps(other) {
+            return None;
+        }
+
+        let mut its = *self;
+        for axis in 0..N {
+            its.min[axis] = its.min[axis].max(other.min[axis]);
+            its.max[axis] = its.max[axis].min(other.max[axis]);
+        }
+        Some(its)
+    }
+
+    /// Cuts the hole out of the box, returning at most 2N disjoint boxes covering the rest.
+    pub fn cut(&self, hole: &Aabb<N>) -> Vec<Aabb<N>> {
+        let Some(its) = self.intersect(hole) else {
+            return vec![*self];
+        };
+
+        // Slice off the slabs below and above the hole one axis at a time. Whatever is left
+        // afterwards is the hole itself.
+        let mut pieces = Vec::new();
+        let mut rest = *self;
+        for axis in 0..N {
+            if rest.min[axis] < its.min[axis] {
+                let mut below = rest;
+                below.max[axis] = its.min[axis] - 1;
+                pieces.push(below);
+            }
+            if rest.max[axis] > its.max[axis] {
+                let mut above = rest;
+                above.min[axis] = its.max[axis] + 1;
+                pieces.push(above);
+            }
+            rest.min[axis] = its.min[axis];
+            rest.max[axis] = its.max[axis];
+        }
+        pieces
+    }
+}
+
+/// A union of boxes, stored as disjoint boxes so volumes just add up.
+#[derive(Clone, Debug, Default)]
+pub struct BoxSet<const N: usize> {
+    boxes: Vec<Aabb<N>>,
+}
+
+impl<const N: usize> BoxSet<N> {
+    pub fn new() -> BoxSet<N> {
+        BoxSet { boxes: Vec::new() }
+    }
+
+    /// The disjoint boxes making up the set, in no particular order.
+    pub fn boxes(&self) -> &[Aabb<N>] {
+        &self.boxes
+    }
+
+    pub fn insert(&mut self, bb: Aabb<N>) {
+        self.remove(&bb);
+        self.boxes.push(bb);
+    }
+
+    /// Takes the box out of the set, splitting up anything it partially covers.
+    pub fn remove(&mut self, bb: &Aabb<N>) {
+        self.boxes = self.boxes.iter().flat_map(|existing| existing.cut(bb)).collect();
+    }
+
+    /// The number of unit cells covered by any of the boxes, or None if that doesn't fit in
+    /// a u128.
+    pub fn volume(&self) -> Option<u128> {
+        total_volume(self.boxes.iter().copied())
+    }
+
+    /// The volume of the part of the set inside the region, or None if that doesn't fit in
+    /// a u128.
+    pub fn clipped_volume(&self, region: &Aabb<N>) -> Option<u128> {
+        total_volume(self.boxes.iter().filter_map(|bb| bb.intersect(region)))
+    }
+}
+
+fn total_volume<const N: usize>(mut boxes: impl Iterator<Item = Aabb<N>>) -> Option<u128> {
+    boxes.try_fold(0u128, |total, bb| total.checked_add(bb.volume()?))
+}
+
+/// The volume of the union of the boxes, counting overlapping parts once. None if that doesn't
+/// fit in a u128.
+pub fn union_volume<const N: usize>(boxes: &[Aabb<N>]) -> Option<u128> {
+    let mut set = BoxSet::new();
+    for bb in boxes {
+        set.insert(*bb);
+    }
+    set.volume()
+}
+
+/// A set of integers stored as sorted, disjoint inclusive ranges. Touching ranges are merged,
+/// so there's only one way to store any set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    intervals: Vec<(i64, i64)>,
+}
+
+impl IntervalSet {
+    pub fn new() -> IntervalSet {
+        IntervalSet::default()
+    }
+
+    pub fn from_intervals(intervals: impl IntoIterator<Item = (i64, i64)>) -> IntervalSet {
+        let mut intervals = intervals.into_iter().filter(|(lo, hi)| lo <= hi).collect::<Vec<_>>();
+        intervals.sort_unstable();
+
+        let mut merged: Vec<(i64, i64)> = Vec::with_capacity(intervals.len());
+        for (lo, hi) in intervals {
+            match merged.last_mut() {
+                Some(last) if lo <= last.1.saturating_add(1) => last.1 = last.1.max(hi),
+                _ => merged.push((lo, hi)),
+            }
+        }
+        IntervalSet { intervals: merged }
+    }
+
+    /// The inclusive ranges in the set, sorted and with gaps between them.
+    pub fn intervals(&self) -> &[(i64, i64)] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The number of integers in the set. That's a u128 since all of i64 has one more than
+    /// fits in a u64.
+    pub fn len(&self) -> u128 {
+        self.intervals.iter().map(|(lo, hi)| hi.abs_diff(*lo) as u128 + 1).sum()
+    }
+
+    pub fn contains(&self, x: i64) -> bool {
+        let idx = self.intervals.partition_point(|(_, hi)| *hi < x);
+        self.intervals.get(idx).is_some_and(|(lo, _)| *lo <= x)
+    }
+
+    pub fn insert(&mut self, lo: i64, hi: i64) {
+        *self = self.union(&IntervalSet::from_intervals([(lo, hi)]));
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        IntervalSet::from_intervals(self.intervals.iter().chain(&other.intervals).copied())
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (self.intervals[i], other.intervals[j]);
+            let (lo, hi) = (a.0.max(b.0), a.1.min(b.1));
+            if lo <= hi {
+                intervals.push((lo, hi));
+            }
+
+            // Whichever ends first can't overlap anything else in the other set.
+            if a.1 < b.1 { i += 1 } else { j += 1 }
+        }
+        IntervalSet { intervals }
+    }
+
+    /// Everything in `lo..=hi` that's not in the set.
+    pub fn complement(&self, (lo, hi): (i64, i64)) -> IntervalSet {
+        let mut intervals = Vec::new();
+        let mut next = lo;
+        for &(start, end) in &self.intervals {
+            if end < next {
+                continue;
+            }
+            if start > hi {
+                break;
+            }
+            if start > next {
+                intervals.push((next, start - 1));
+            }
+            if end >= hi {
+                return IntervalSet { intervals };
+            }
+            next = end + 1;
+        }
+        if next <= hi {
+            intervals.push((next, hi));
+        }
+        IntervalSet { intervals }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube(min: i64, max: i64) -> Aabb<3> {
+        Aabb::new([min; 3], [max; 3])
+    }
+
+    /// Checks the pieces are disjoint, stay inside the box and avoid the hole.
+    fn check_cut(from: Aabb<3>, hole: Aabb<3>, expected_pieces: usize) {
+        let pieces = from.cut(&hole);
+        assert_eq!(expected_pieces, pieces.len());
+
+        let removed = from.intersect(&hole).map_or(0, |its| its.volume().unwrap());
+        assert_eq!(from.volume().unwrap() - removed, pieces.iter().map(|piece| piece.volume().unwrap()).sum());
+        for (i, piece) in pieces.iter().enumerate() {
+            assert_eq!(Some(*piece), piece.intersect(&from));
+            assert!(!piece.overlaps(&hole));
+            assert!(pieces[i + 1..].iter().all(|other| !other.overlaps(piece)));
+        }
+    }
+
+    #[test]
+    fn cuts() {
+        check_cut(cube(0, 5), cube(2, 3), 6);
+        check_cut(cube(0, 5), Aabb::new([0, 0, 0], [2, 5, 5]), 1);
+        check_cut(cube(0, 5), Aabb::new([3, 0, 0], [5, 5, 5]), 1);
+        check_cut(cube(-5, 0), Aabb::new([-5, -5, -5], [-2, 0, 0]), 1);
+        check_cut(cube(0, 5), cube(-10, 10), 0);
+        check_cut(cube(0, 5), cube(6, 7), 1);
+    }
+
+    #[test]
+    fn higher_dimensions() {
+        let tesseract = Aabb::new([0; 4], [2; 4]);
+        assert_eq!(Some(81), tesseract.volume());
+        assert_eq!(8, tesseract.cut(&Aabb::new([1; 4], [1; 4])).len());
+        let mut set = BoxSet::new();
+        set.insert(tesseract);
+        set.remove(&Aabb::new([1; 4], [1; 4]));
+        assert_eq!(Some(80), set.volume());
+        assert!(tesseract.contains([0, 1, 2, 2]) && !tesseract.contains([0, 1, 3, 2]));
+    }
+
+    #[test]
+    fn box_sets() {
+        let mut set = BoxSet::new();
+        set.insert(cube(10, 12));
+        assert_eq!(Some(27), set.volume());
+        set.insert(cube(11, 13));
+        assert_eq!(Some(46), set.volume());
+        set.remove(&cube(9, 11));
+        assert_eq!(Some(38), set.volume());
+        set.insert(cube(10, 10));
+        assert_eq!(Some(39), set.volume());
+
+        assert_eq!(Some(8), set.clipped_volume(&cube(12, 20)));
+        assert_eq!(Some(46), union_volume(&[cube(10, 12), cube(11, 13)]));
+        assert_eq!(Some(2), union_volume(&[Aabb::new([0], [0]), Aabb::new([0], [1])]));
+    }
+
+    #[test]
+    fn huge_volumes() {
+        assert_eq!(Some(1 << 64), Aabb::new([i64::MIN], [i64::MAX]).volume());
+        assert_eq!(None, Aabb::new([i64::MIN; 2], [i64::MAX; 2]).volume());
+        assert_eq!(Some(1 << 127), Aabb::new([i64::MIN; 2], [i64::MAX, -1]).volume());
+        assert_eq!(None, union_volume(&[Aabb::new([i64::MIN; 2], [i64::MAX, -1]), Aabb::new([i64::MIN, 0], [i64::MAX; 2])]));
+    }
+
+    #[test]
+    fn intervals() {
+        let a = IntervalSet::from_intervals([(5, 8), (1, 2), (3, 3), (10, 12), (7, 9)]);
+        assert_eq!(&[(1, 3), (5, 12)], a.intervals());
+        assert_eq!(11, a.len());
+        assert!(a.contains(3) && !a.contains(4) && a.contains(12) && !a.contains(0));
+
+        let b = IntervalSet::from_intervals([(0, 1), (6, 6), (11, 20)]);
+        assert_eq!(&[(1, 1), (6, 6), (11, 12)], a.intersection(&b).intervals());
+        assert_eq!(&[(0, 3), (5, 20)], a.union(&b).intervals());
+        assert_eq!(&[(0, 0), (4, 4), (13, 15)], a.complement((0, 15)).intervals());
+        assert_eq!(&[(4, 4)], a.complement((2, 10)).intervals());
+        assert!(a.complement((1, 3)).is_empty());
+
+        let mut c = IntervalSet::new();
+        c.insert(i64::MIN, -1);
+        c.insert(0, i64::MAX);
+        assert_eq!(&[(i64::MIN, i64::MAX)], c.intervals());
+        assert_eq!(1 << 64, c.len());
+        assert_eq!(a, a.complement((-100, 100)).complement((-100, 100)));
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::{HashMap, VecDeque};
+
+pub type NodeId = usize;
+
+/// A directed graph with nodes looked up by name. Every edge carries a weight of type `W`,
+/// which is `()` for unweighted graphs.
+#[derive(Clone, Debug)]
+pub struct Graph<W = ()> {
+    names: Vec<String>,
+    ids: HashMap<String, NodeId>,
+    edges: Vec<Vec<(NodeId, W)>>,
+}
+
+impl<W> Default for Graph<W> {
+    fn default() -> Graph<W> {
+        Graph { names: Vec::new(), ids: HashMap::new(), edges: Vec::new() }
+    }
+}
+
+/// Decides whether a path enumerated by [`Graph::for_each_path`] may step into a node.
+/// The hooks let rules keep state about the current path, e.g. "one node may be visited twice".
+pub trait RevisitRule {
+    /// Whether the path may step into `node`, which it already visited `visits` times.
+    fn allows(&self, node: NodeId, visits: usize) -> bool;
+
+    /// Called when the path steps into `node`, with the visits before this one.
+    fn enter(&mut self, _node: NodeId, _visits: usize) {}
+
+    /// Called when the path backs out of `node` again, with the visits that are left.
+    fn leave(&mut self, _node: NodeId, _visits: usize) {}
+}
+
+/// Stateless rules can be plain closures.
+impl<F: Fn(NodeId, usize) -> bool> RevisitRule for F {
+    fn allows(&self, node: NodeId, visits: usize) -> bool {
+        self(node, visits)
+    }
+}
+
+/// Paths that never visit a node twice.
+pub fn simple(_node: NodeId, visits: usize) -> bool {
+    visits == 0
+}
+
+impl<W> Graph<W> {
+    pub fn new() -> Graph<W> {
+        Graph::default()
+    }
+
+    /// Returns the id of the named node, adding it if it's new.
+    pub fn intern(&mut self, name: &str) -> NodeId {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.edges.push(Vec::new());
+        id
+    }
+
+    pub fn id(&self, name: &str) -> Option<NodeId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.names[id]
+    }
+
+    /// The number of nodes.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn nodes(&self) -> std::ops::Range<NodeId> {
+        0..self.len()
+    }
+
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: W) {
+        self.edges[from].push((to, weight));
+    }
+
+    /// The outgoing edges of a node with their weights, in the order they were added.
+    pub fn edges(&self, id: NodeId) -> &[(NodeId, W)] {
+        &self.edges[id]
+    }
+
+    pub fn neighbors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.edges[id].iter().map(|(to, _)| *to)
+    }
+
+    /// The number of edges from the start to every node, or None for the unreachable ones.
+    pub fn bfs(&self, start: NodeId) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.len()];
+        distances[start] = Some(0);
+
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[node].map(|d| d + 1);
+            for next in self.neighbors(node) {
+                if distances[next].is_none() {
+                    distances[next] = distance;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// The nodes reachable from the start in depth first pre-order.
+    pub fn dfs(&self, start: NodeId) -> Vec<NodeId> {
+        let mut order = Vec::new();
+        let mut seen = vec![false; self.len()];
+
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            if std::mem::replace(&mut seen[node], true) {
+                continue;
+            }
+            order.push(node);
+
+            // Reversed, so the first edge gets explored first.
+            stack.extend(self.edges[node].iter().rev().map(|(to, _)| *to).filter(|to| !seen[*to]));
+        }
+
+        order
+    }
+
+    /// Orders the nodes so every edge points forward, or None if there's a cycle.
+    pub fn toposort(&self) -> Option<Vec<NodeId>> {
+        let mut in_degrees = vec![0; self.len()];
+        for node in self.nodes() {
+            for next in self.neighbors(node) {
+                in_degrees[next] += 1;
+            }
+        }
+
+        let mut ready = self.nodes().filter(|node| in_degrees[*node] == 0).collect::<VecDeque<_>>();
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(node) = ready.pop_front() {
+            order.push(node);
+            for next in self.neighbors(node) {
+                in_degrees[next] -= 1;
+                if in_degrees[next] == 0 {
+                    ready.push_back(next);
+                }
+            }
+        }
+
+        (order.len() == self.len()).then_some(order)
+    }
+
+    /// Groups the nodes that are connected, ignoring the direction of the edges.
+    /// The groups and the nodes in them are sorted by id.
+    pub fn components(&self) -> Vec<Vec<NodeId>> {
+        fn root(parents: &mut [NodeId], mut node: NodeId) -> NodeId {
+            while parents[node] != node {
+                parents[node] = parents[parents[node]];
+                node = parents[node];
+            }
+            node
+        }
+
+        let mut parents = self.nodes().collect::<Vec<_>>();
+        for node in self.nodes() {
+            for next in self.neighbors(node) {
+                let (a, b) = (root(&mut parents, node), root(&mut parents, next));
+                parents[a.max(b)] = a.min(b);
+            }
+        }
+
+        let mut components: Vec<Vec<NodeId>> = Vec::new();
+        let mut component_of_root = HashMap::new();
+        for node in self.nodes() {
+            let idx = *component_of_root.entry(root(&mut parents, node)).or_insert_with(|| {
+                components.push(Vec::new());
+                components.len() - 1
+            });
+            components[idx].push(node);
+        }
+
+        components
+    }
+
+    /// Calls `visit` with every path from start to end the rule allows. The start counts as
+    /// visited once, and paths stop as soon as they reach the end. The rule has to limit
+    /// revisits on every cycle, or this never returns.
+    pub fn for_each_path(&self, start: NodeId, end: NodeId, rule: &mut impl RevisitRule,
+                         mut visit: impl FnMut(&[NodeId])) {
+        let mut visits = vec![0; self.len()];
+        visits[start] = 1;
+        let mut path = vec![start];
+        self.extend_paths(end, rule, &mut visits, &mut path, &mut visit);
+    }
+
+    fn extend_paths(&self, end: NodeId, rule: &mut impl RevisitRule, visits: &mut [usize],
+                    path: &mut Vec<NodeId>, visit: &mut impl FnMut(&[NodeId])) {
+        let node = *path.last().expect("Paths are never empty");
+        if node == end {
+            visit(path);
+            return;
+        }
+
+        for next in self.neighbors(node) {
+            if !rule.allows(next, visits[next]) {
+                continue;
+            }
+
+            rule.enter(next, visits[next]);
+            visits[next] += 1;
+            path.push(next);
+
+            self.extend_paths(end, rule, visits, path, visit);
+
+            path.pop();
+            visits[next] -= 1;
+            rule.leave(next, visits[next]);
+        }
+    }
+
+    /// Counts the paths [`Graph::for_each_path`] would visit.
+    pub fn count_paths(&self, start: NodeId, end: NodeId, rule: &mut impl RevisitRule) -> usize {
+        let mut count = 0;
+        self.for_each_path(start, end, rule, |_| count += 1);
+        count
+    }
+}
+
+impl<W: Clone> Graph<W> {
+    /// Adds an edge in both directions.
+    pub fn add_undirected_edge(&mut self, a: NodeId, b: NodeId, weight: W) {
+        self.add_edge(a, b, weight.clone());
+        self.add_edge(b, a, weight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &str)]) -> Graph {
+        let mut graph = Graph::new();
+        for (from, to) in edges {
+            let (from, to) = (graph.intern(from), graph.intern(to));
+            graph.add_edge(from, to, ());
+        }
+        graph
+    }
+
+    #[test]
+    fn interning() {
+        let mut graph = Graph::<u32>::new();
+        let a = graph.intern("a");
+        let b = graph.intern("b");
+        assert_eq!(a, graph.intern("a"));
+        assert_eq!(Some(b), graph.id("b"));
+        assert_eq!(None, graph.id("c"));
+        assert_eq!("b", graph.name(b));
+
+        graph.add_undirected_edge(a, b, 7);
+        assert_eq!(&[(b, 7)], graph.edges(a));
+        assert_eq!(&[(a, 7)], graph.edges(b));
+    }
+
+    #[test]
+    fn traversals() {
+        let graph = graph(&[("a", "b"), ("a", "c"), ("b", "d"), ("c", "d"), ("e", "f")]);
+        let [a, b, c, d, e, f] = ["a", "b", "c", "d", "e", "f"].map(|name| graph.id(name).unwrap());
+
+        assert_eq!(vec![Some(0), Some(1), Some(1), Some(2), None, None], graph.bfs(a));
+        assert_eq!(vec![a, b, d, c], graph.dfs(a));
+        assert_eq!(Some(vec![a, e, b, c, f, d]), graph.toposort());
+        assert_eq!(vec![vec![a, b, c, d], vec![e, f]], graph.components());
+
+        let cyclic = self::graph(&[("a", "b"), ("b", "a")]);
+        assert_eq!(None, cyclic.toposort());
+    }
+
+    #[test]
+    fn paths() {
+        let mut graph = Graph::new();
+        let [a, b, c, d] = ["a", "b", "c", "d"].map(|name| graph.intern(name));
+        graph.add_undirected_edge(a, b, ());
+        graph.add_undirected_edge(b, c, ());
+        graph.add_undirected_edge(b, d, ());
+        graph.add_undirected_edge(a, c, ());
+
+        let mut paths = Vec::new();
+        graph.for_each_path(a, c, &mut simple, |path| paths.push(path.to_vec()));
+        assert_eq!(vec![vec![a, b, c], vec![a, c]], paths);
+
+        // Coming back through b is the only way out of the dead end at d.
+        assert_eq!(3, graph.count_paths(a, c, &mut |node, visits| visits == 0 || node == b));
+    }
+}
@@ -0,0 +1,336 @@
+use crate::error::{AocError, Result};
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// Offsets of the 4 orthogonal neighbors.
+pub const NEIGHBORS_4: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// Offsets of the 8 neighbors including diagonals.
+pub const NEIGHBORS_8: [(isize, isize); 8] = [
+    (-1, -1), (0, -1), (1, -1),
+    (-1,  0),          (1,  0),
+    (-1,  1), (0,  1), (1,  1),
+];
+
+/// The 3x3 window around a cell including itself, in row-major order.
+pub const WINDOW_3X3: [(isize, isize); 9] = [
+    (-1, -1), (0, -1), (1, -1),
+    (-1,  0), (0,  0), (1,  0),
+    (-1,  1), (0,  1), (1,  1),
+];
+
+/// How to treat neighbors that fall outside of a grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edges<T> {
+    /// There is nothing outside the grid, those neighbors are skipped.
+    Clip,
+    /// The grid wraps around to the opposite side, like a torus.
+    Wrap,
+    /// Everything outside the grid has this value. Those neighbors have a value but no position.
+    Default(T),
+}
+
+/// A rectangular grid stored row-major. Positions are (x, y) with (0, 0) in the top left.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Grid<T> {
+        assert_eq!(width * height, cells.len(), "Grid cells don't match its size");
+        Grid { cells, width, height }
+    }
+
+    /// Builds a grid by calling `f(x, y)` for every position, row by row.
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(usize, usize) -> T) -> Grid<T> {
+        let cells = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Grid { cells, width, height }
+    }
+
+    /// Parses one row per non-empty line, mapping each char to a cell.
+    /// Fails on chars `parse_cell` rejects and on rows of different widths.
+    pub fn parse_with(input: &str, mut parse_cell: impl FnMut(char) -> Option<T>) -> Result<Grid<T>> {
+        let mut cells = Vec::with_capacity(input.len());
+        let mut width = 0;
+        let mut height = 0;
+
+        for (line_idx, line) in input.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut row_len = 0;
+            for (col, c) in line.chars().enumerate() {
+                let cell = parse_cell(c).ok_or_else(|| {
+                    AocError::parse(line_idx + 1, col + 1, format!("Unexpected char '{}' in grid", c))
+                })?;
+                cells.push(cell);
+                row_len += 1;
+            }
+
+            if height == 0 {
+                width = row_len;
+            } else if row_len != width {
+                let col = row_len.min(width) + 1;
+                return Err(AocError::parse(line_idx + 1, col, format!("Expected rows of width {}", width)));
+            }
+            height += 1;
+        }
+
+        if height == 0 {
+            return Err(AocError::parse(1, 1, "Expected a grid"));
+        }
+
+        Ok(Grid { cells, width, height })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The row-major index of a position, as used by `cells`.
+    pub fn idx(&self, x: usize, y: usize) -> usize {
+        x + y * self.width
+    }
+
+    /// The position of a row-major index.
+    pub fn xy(&self, idx: usize) -> (usize, usize) {
+        (idx % self.width, idx / self.width)
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        (x < self.width && y < self.height).then(|| &self.cells[self.idx(x, y)])
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if x < self.width && y < self.height {
+            let idx = self.idx(x, y);
+            Some(&mut self.cells[idx])
+        } else {
+            None
+        }
+    }
+
+    /// All cells in row-major order.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    /// All positions in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// All cells along with their positions, in row-major order.
+    pub fn enumerate(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> std::slice::Chunks<'_, T> {
+        // chunks() doesn't take 0, an empty grid has no rows either way.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "Column {} is outside the grid", x);
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// Moves a position by an offset, returning None if it ends up outside the grid.
+    pub fn offset(&self, (x, y): (usize, usize), (dx, dy): (isize, isize)) -> Option<(usize, usize)> {
+        let x = x.checked_add_signed(dx).filter(|x| *x < self.width)?;
+        let y = y.checked_add_signed(dy).filter(|y| *y < self.height)?;
+        Some((x, y))
+    }
+
+    /// The position of the neighbor at an offset, or None if it has none under the edge policy.
+    pub fn neighbor_pos(&self, pos: (usize, usize), offset: (isize, isize), edges: &Edges<T>) -> Option<(usize, usize)> {
+        match edges {
+            Edges::Wrap => {
+                let wrap = |v: usize, dv: isize, len: usize| (v as isize + dv).rem_euclid(len as isize) as usize;
+                Some((wrap(pos.0, offset.0, self.width), wrap(pos.1, offset.1, self.height)))
+            },
+            Edges::Clip | Edges::Default(_) => self.offset(pos, offset),
+        }
+    }
+
+    /// The value of the neighbor at an offset, or None if it's clipped.
+    pub fn neighbor_value<'a>(&'a self, pos: (usize, usize), offset: (isize, isize), edges: &'a Edges<T>) -> Option<&'a T> {
+        match (self.neighbor_pos(pos, offset, edges), edges) {
+            (Some(neighbor), _) => Some(&self[neighbor]),
+            (None, Edges::Default(value)) => Some(value),
+            (None, _) => None,
+        }
+    }
+
+    /// The positions of the neighbors at each offset in the stencil, see [`Grid::neighbor_pos`].
+    pub fn neighbors<'a>(&'a self, pos: (usize, usize), stencil: &'a [(isize, isize)], edges: &'a Edges<T>)
+        -> impl Iterator<Item = (usize, usize)> + 'a
+    {
+        stencil.iter().filter_map(move |offset| self.neighbor_pos(pos, *offset, edges))
+    }
+
+    /// The values of the neighbors at each offset in the stencil, see [`Grid::neighbor_value`].
+    pub fn neighbor_values<'a>(&'a self, pos: (usize, usize), stencil: &'a [(isize, isize)], edges: &'a Edges<T>)
+        -> impl Iterator<Item = &'a T> + 'a
+    {
+        stencil.iter().filter_map(move |offset| self.neighbor_value(pos, *offset, edges))
+    }
+
+    /// The orthogonal neighbors inside the grid.
+    pub fn neighbors4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbors(pos, &NEIGHBORS_4, &Edges::Clip)
+    }
+
+    /// The orthogonal and diagonal neighbors inside the grid.
+    pub fn neighbors8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbors(pos, &NEIGHBORS_8, &Edges::Clip)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid { cells: self.cells.iter().map(f).collect(), width: self.width, height: self.height }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid { cells: vec![fill; width * height], width, height }
+    }
+}
+
+impl Grid<u8> {
+    /// Parses a grid of single digits.
+    pub fn parse_digits(input: &str) -> Result<Grid<u8>> {
+        Grid::parse_with(input, |c| c.to_digit(10).map(|d| d as u8))
+    }
+}
+
+impl Grid<char> {
+    pub fn parse_chars(input: &str) -> Result<Grid<char>> {
+        Grid::parse_with(input, Some)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y).expect("Position is outside the grid")
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        self.get_mut(x, y).expect("Position is outside the grid")
+    }
+}
+
+/// Prints one line per row with the cells next to each other.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_index() {
+        let grid = Grid::parse_digits("123\n456\n").unwrap();
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!(Some(&6), grid.get(2, 1));
+        assert_eq!(None, grid.get(3, 0));
+        assert_eq!(None, grid.get(0, 2));
+        assert_eq!(4, grid[(0, 1)]);
+        assert_eq!((1, 1), grid.xy(grid.idx(1, 1)));
+        assert_eq!("123\n456\n", grid.to_string());
+    }
+
+    #[test]
+    fn parse_errors() {
+        match Grid::parse_digits("12\n3x\n") {
+            Err(AocError::Parse { line, column, .. }) => assert_eq!((2, 2), (line, column)),
+            other => panic!("Unexpected {:?}", other),
+        }
+        match Grid::parse_digits("123\n45\n") {
+            Err(AocError::Parse { line, column, .. }) => assert_eq!((2, 3), (line, column)),
+            other => panic!("Unexpected {:?}", other),
+        }
+        assert!(Grid::parse_chars("\n\n").is_err());
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = Grid::from_fn(3, 2, |x, y| x + 10 * y);
+        assert_eq!(vec![&[0, 1, 2][..], &[10, 11, 12][..]], grid.rows().collect::<Vec<_>>());
+        assert_eq!(vec![1, 11], grid.column(1).copied().collect::<Vec<_>>());
+        assert_eq!(3, grid.columns().count());
+        assert_eq!(Some(((2, 1), &12)), grid.enumerate().last());
+        assert_eq!(Grid::from_vec(3, 2, vec![0, 2, 4, 20, 22, 24]), grid.map(|v| v * 2));
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = Grid::new(3, 3, 0);
+        assert_eq!(2, grid.neighbors4((0, 0)).count());
+        assert_eq!(4, grid.neighbors4((1, 1)).count());
+        assert_eq!(3, grid.neighbors8((2, 2)).count());
+        assert_eq!(8, grid.neighbors8((1, 1)).count());
+
+        let knight = [(1, 2), (2, 1), (-1, 2)];
+        assert_eq!(vec![(1, 2), (2, 1)], grid.neighbors((0, 0), &knight, &Edges::Clip).collect::<Vec<_>>());
+        assert_eq!(None, grid.offset((0, 0), (-1, 0)));
+    }
+
+    #[test]
+    fn edge_policies() {
+        let grid = Grid::from_fn(3, 2, |x, y| x + 10 * y);
+
+        assert_eq!(Some((2, 1)), grid.neighbor_pos((0, 0), (-1, -1), &Edges::Wrap));
+        assert_eq!(Some((0, 0)), grid.neighbor_pos((2, 1), (4, 3), &Edges::Wrap));
+        assert_eq!(None, grid.neighbor_pos((0, 0), (-1, 0), &Edges::Default(7)));
+
+        let clipped = grid.neighbor_values((0, 0), &NEIGHBORS_8, &Edges::Clip).copied().collect::<Vec<_>>();
+        assert_eq!(vec![1, 10, 11], clipped);
+
+        let wrapped = grid.neighbor_values((0, 0), &NEIGHBORS_4, &Edges::Wrap).copied().collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 10, 10], wrapped);
+
+        let window = grid.neighbor_values((0, 0), &WINDOW_3X3, &Edges::Default(7)).copied().collect::<Vec<_>>();
+        assert_eq!(vec![7, 7, 7, 7, 0, 1, 7, 10, 11], window);
+    }
+}
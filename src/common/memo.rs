@@ -0,0 +1,101 @@
+use super::FastHasher;
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hash};
+
+/// How well a [`Memo`] is doing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+    /// The number of stored results.
+    pub len: usize,
+}
+
+/// Remembers the results of a function, mostly for recursive solvers.
+#[derive(Clone, Debug)]
+pub struct Memo<K, V> {
+    entries: HashMap<K, V, BuildHasherDefault<FastHasher>>,
+    max_len: Option<usize>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<K: Hash + Eq, V: Clone> Default for Memo<K, V> {
+    fn default() -> Memo<K, V> {
+        Memo { entries: HashMap::default(), max_len: None, hits: 0, misses: 0 }
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> Memo<K, V> {
+    pub fn new() -> Memo<K, V> {
+        Memo::default()
+    }
+
+    /// A memo that stores at most `max_len` results. Once full, results are still computed
+    /// but no longer stored.
+    pub fn bounded(max_len: usize) -> Memo<K, V> {
+        Memo { max_len: Some(max_len), ..Memo::default() }
+    }
+
+    /// Returns the stored result for the key, or computes and stores it. `compute` gets the memo
+    /// passed back in, so it can recurse through it.
+    pub fn get_or_compute(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.entries.get(&key) {
+            self.hits += 1;
+            return value.clone();
+        }
+
+        self.misses += 1;
+        let value = compute(self);
+        if self.max_len.is_none_or(|max_len| self.entries.len() < max_len) {
+            self.entries.insert(key, value.clone());
+        }
+        value
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(key)
+    }
+
+    /// Forgets all results, but keeps the statistics.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats { hits: self.hits, misses: self.misses, len: self.entries.len() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fib(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        memo.get_or_compute(n, |memo| fib(n - 1, memo) + fib(n - 2, memo))
+    }
+
+    #[test]
+    fn recursion() {
+        let mut memo = Memo::new();
+        assert_eq!(12_586_269_025, fib(50, &mut memo));
+        assert_eq!(MemoStats { hits: 47, misses: 49, len: 49 }, memo.stats());
+
+        assert_eq!(Some(&55), memo.get(&10));
+        memo.clear();
+        assert_eq!(None, memo.get(&10));
+        assert_eq!(47, memo.stats().hits);
+    }
+
+    #[test]
+    fn bounded() {
+        let mut memo = Memo::bounded(20);
+        assert_eq!(832_040, fib(30, &mut memo));
+        assert_eq!(20, memo.stats().len);
+        assert_eq!(Some(&1), memo.get(&2));
+        assert_eq!(None, memo.get(&30));
+    }
+}
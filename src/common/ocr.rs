@@ -0,0 +1,139 @@
+use super::Grid;
+use crate::error::{AocError, Result};
+
+/// A fixed width font that puzzles draw their answers in. The glyphs are kept as a sheet,
+/// drawn the same way the puzzles draw them.
+pub struct Font {
+    pub width: usize,
+    pub height: usize,
+    /// The distance from one letter to the next, counting the blank columns in between.
+    pub pitch: usize,
+    letters: &'static str,
+    sheet: &'static str,
+}
+
+/// The letters most puzzles use, 4 pixels wide and 6 high.
+pub const FONT_4X6: Font = Font { width: 4, height: 6, pitch: 5, letters: "ABCEFGHIJKLOPRSUZ", sheet: "\
+.##..###...##..####.####..##..#..#..###...##.#..#.#.....##..###..###...###.#..#.####
+#..#.#..#.#..#.#....#....#..#.#..#...#.....#.#.#..#....#..#.#..#.#..#.#....#..#....#
+#..#.###..#....###..###..#....####...#.....#.##...#....#..#.#..#.#..#.#....#..#...#.
+####.#..#.#....#....#....#.##.#..#...#.....#.#.#..#....#..#.###..###...##..#..#..#..
+#..#.#..#.#..#.#....#....#..#.#..#...#..#..#.#.#..#....#..#.#....#.#.....#.#..#.#...
+#..#.###...##..####.#.....###.#..#..###..##..#..#.####..##..#....#..#.###...##..####
+" };
+
+/// The larger letters some puzzles use, 6 pixels wide and 10 high.
+pub const FONT_6X10: Font = Font { width: 6, height: 10, pitch: 8, letters: "ABCEFGHJKLNPRXZ", sheet: "\
+..##....#####....####...######..######...####...#....#.....###..#....#..#.......#....#..#####...#####...#....#..######
+.#..#...#....#..#....#..#.......#.......#....#..#....#......#...#...#...#.......##...#..#....#..#....#..#....#.......#
+#....#..#....#..#.......#.......#.......#.......#....#......#...#..#....#.......##...#..#....#..#....#...#..#........#
+#....#..#....#..#.......#.......#.......#.......#....#......#...#.#.....#.......#.#..#..#....#..#....#...#..#.......#.
+#....#..#####...#.......#####...#####...#.......######......#...##......#.......#.#..#..#####...#####.....##.......#..
+######..#....#..#.......#.......#.......#..###..#....#......#...##......#.......#..#.#..#.......#..#......##......#...
+#....#..#....#..#.......#.......#.......#....#..#....#......#...#.#.....#.......#..#.#..#.......#...#....#..#....#....
+#....#..#....#..#.......#.......#.......#....#..#....#..#...#...#..#....#.......#...##..#.......#...#....#..#...#.....
+#....#..#....#..#....#..#.......#.......#...##..#....#..#...#...#...#...#.......#...##..#.......#....#..#....#..#.....
+#....#..#####....####...######..#........###.#..#....#...###....#....#..######..#....#..#.......#....#..#....#..######
+" };
+
+impl Font {
+    /// The font that's `height` pixels high, if there is one.
+    pub fn for_height(height: usize) -> Option<&'static Font> {
+        [&FONT_4X6, &FONT_6X10].into_iter().find(|font| font.height == height)
+    }
+
+    /// The glyph with its left edge at `left` as bits, row by row starting at the top left.
+    /// Pixels outside the grid are dark.
+    fn glyph_at(&self, pixels: &Grid<bool>, left: usize) -> u64 {
+        let mut bits = 0;
+        for y in 0..self.height {
+            for x in left..left + self.width {
+                bits = (bits << 1) | *pixels.get(x, y).unwrap_or(&false) as u64;
+            }
+        }
+        bits
+    }
+
+    fn render(&self, glyph: u64) -> String {
+        let last_bit = self.width * self.height - 1;
+        Grid::from_fn(self.width, self.height, |x, y| {
+            if glyph & (1 << (last_bit - (y * self.width + x))) != 0 { '#' } else { '.' }
+        }).to_string()
+    }
+
+    /// Reads the letters drawn in the grid, the first one starting at its left edge. Blank
+    /// glyphs read as spaces, except at the end.
+    pub fn read(&self, pixels: &Grid<bool>) -> Result<String> {
+        let sheet = Grid::parse_with(self.sheet, |c| Some(c == '#')).expect("Font sheets are valid grids");
+        let known = self.letters.chars().enumerate()
+            .map(|(idx, letter)| (self.glyph_at(&sheet, idx * self.pitch), letter))
+            .collect::<Vec<_>>();
+
+        let mut text = String::new();
+        for idx in 0..pixels.width().div_ceil(self.pitch) {
+            let glyph = self.glyph_at(pixels, idx * self.pitch);
+            match known.iter().find(|(bits, _)| *bits == glyph) {
+                Some((_, letter)) => text.push(*letter),
+                None if glyph == 0 => text.push(' '),
+                None => return Err(AocError::no_solution(
+                    format!("Unknown glyph for letter {}:\n{}", idx + 1, self.render(glyph)))),
+            }
+        }
+        Ok(text.trim_end().to_string())
+    }
+}
+
+/// Reads the letters in the grid, in whichever font is as high as the grid.
+pub fn read_text(pixels: &Grid<bool>) -> Result<String> {
+    match Font::for_height(pixels.height()) {
+        Some(font) => font.read(pixels),
+        None => Err(AocError::no_solution(format!("No font is {} pixels high", pixels.height()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixels(text: &str) -> Grid<bool> {
+        Grid::parse_with(text, |c| Some(c == '#')).unwrap()
+    }
+
+    #[test]
+    fn alphabets() {
+        for font in [&FONT_4X6, &FONT_6X10] {
+            assert_eq!(font.letters, read_text(&pixels(font.sheet)).unwrap());
+        }
+    }
+
+    #[test]
+    fn spaces_and_padding() {
+        // "HI U", without the gap after the last letter.
+        let text = pixels("\
+#..#..###......#..#
+#..#...#.......#..#
+####...#.......#..#
+#..#...#.......#..#
+#..#...#.......#..#
+#..#..###.......##.
+");
+        assert_eq!("HI U", read_text(&text).unwrap());
+        assert_eq!("HI", read_text(&Grid::from_fn(14, 6, |x, y| x < 10 && text[(x, y)])).unwrap());
+    }
+
+    #[test]
+    fn unknown_glyphs() {
+        let text = pixels("\
+.##..#...
+#..#.#...
+#..#.#...
+#..#.#...
+#..#.#...
+.##..###.
+");
+        let err = read_text(&text).unwrap_err().to_string();
+        assert!(err.ends_with("Unknown glyph for letter 2:\n#...\n#...\n#...\n#...\n#...\n###.\n"), "{}", err);
+
+        assert!(read_text(&pixels("#\n#\n#")).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use super::vec::{Scalar, Vec3};
+
+/// A rotation that maps the axes onto axes, like turning a cube in your hand. There are 24
+/// of them. Each one is an integer matrix with a single 1 or -1 per row and column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rotation {
+    matrix: [[i8; 3]; 3],
+}
+
+impl Default for Rotation {
+    fn default() -> Rotation {
+        Rotation::IDENTITY
+    }
+}
+
+impl Rotation {
+    pub const IDENTITY: Rotation = Rotation { matrix: [[1, 0, 0], [0, 1, 0], [0, 0, 1]] };
+
+    /// All 24 rotations, starting with the identity.
+    pub fn all() -> Vec<Rotation> {
+        const PERMUTATIONS: [[usize; 3]; 6] = [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+
+        let mut rotations = Vec::with_capacity(24);
+        for axes in PERMUTATIONS {
+            for signs in 0..8 {
+                let mut matrix = [[0; 3]; 3];
+                for (row, axis) in axes.iter().enumerate() {
+                    matrix[row][*axis] = if signs & (1 << row) == 0 { 1 } else { -1 };
+                }
+
+                // The other half are mirror images, which no amount of turning gets you to.
+                let rotation = Rotation { matrix };
+                if rotation.determinant() == 1 {
+                    rotations.push(rotation);
+                }
+            }
+        }
+        rotations
+    }
+
+    pub fn matrix(&self) -> [[i8; 3]; 3] {
+        self.matrix
+    }
+
+    fn determinant(&self) -> i8 {
+        let m = &self.matrix;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn apply<T: Scalar>(&self, v: Vec3<T>) -> Vec3<T> {
+        let mut out = Vec3::<T>::zero();
+        for (row, coefficients) in self.matrix.iter().enumerate() {
+            for (axis, coefficient) in coefficients.iter().enumerate() {
+                match coefficient {
+                    1 => out[row] = out[row] + v[axis],
+                    -1 => out[row] = out[row] - v[axis],
+                    _ => {},
+                }
+            }
+        }
+        out
+    }
+
+    /// The rotation that applies this one first, then `next`.
+    pub fn then(&self, next: Rotation) -> Rotation {
+        let mut matrix = [[0; 3]; 3];
+        for (row, out_row) in matrix.iter_mut().enumerate() {
+            for (col, out) in out_row.iter_mut().enumerate() {
+                *out = (0..3).map(|k| next.matrix[row][k] * self.matrix[k][col]).sum();
+            }
+        }
+        Rotation { matrix }
+    }
+
+    /// The rotation that undoes this one. For rotations that's just the transpose.
+    pub fn inverse(&self) -> Rotation {
+        let mut matrix = [[0; 3]; 3];
+        for (row, out_row) in matrix.iter_mut().enumerate() {
+            for (col, out) in out_row.iter_mut().enumerate() {
+                *out = self.matrix[col][row];
+            }
+        }
+        Rotation { matrix }
+    }
+}
+
+/// A rotation followed by a translation, which moves points between coordinate systems
+/// that only differ in where they are and which way they face.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Transform<T> {
+    pub rotation: Rotation,
+    pub translation: Vec3<T>,
+}
+
+impl<T: Scalar> Transform<T> {
+    pub fn new(rotation: Rotation, translation: Vec3<T>) -> Transform<T> {
+        Transform { rotation, translation }
+    }
+
+    pub fn identity() -> Transform<T> {
+        Transform::default()
+    }
+
+    pub fn apply(&self, v: Vec3<T>) -> Vec3<T> {
+        self.rotation.apply(v) + self.translation
+    }
+
+    /// The transform that applies this one first, then `next`. Chaining the transforms from
+    /// A to B and from B to C gives the one from A to C.
+    pub fn then(&self, next: &Transform<T>) -> Transform<T> {
+        Transform {
+            rotation: self.rotation.then(next.rotation),
+            translation: next.apply(self.translation),
+        }
+    }
+
+    pub fn inverse(&self) -> Transform<T> {
+        let rotation = self.rotation.inverse();
+        Transform { rotation, translation: Vec3::zero() - rotation.apply(self.translation) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn group() {
+        let rotations = Rotation::all();
+        assert_eq!(24, rotations.iter().collect::<HashSet<_>>().len());
+        assert_eq!(Rotation::IDENTITY, rotations[0]);
+
+        // A point with distinct coordinates ends up somewhere different for every rotation.
+        let v = Vec3::new(1, 2, 3);
+        assert_eq!(24, rotations.iter().map(|r| r.apply(v)).collect::<HashSet<_>>().len());
+
+        for a in &rotations {
+            assert_eq!(Rotation::IDENTITY, a.then(a.inverse()));
+            for b in &rotations {
+                assert!(rotations.contains(&a.then(*b)));
+                assert_eq!(b.apply(a.apply(v)), a.then(*b).apply(v));
+            }
+        }
+    }
+
+    #[test]
+    fn quarter_turn() {
+        // Turning a quarter around z takes x to y, and four turns get back to the start.
+        let turn = Rotation::all().into_iter().find(|r| r.apply(Vec3::new(1, 0, 0)) == Vec3::new(0, 1, 0)
+                                                       && r.apply(Vec3::new(0, 0, 1)) == Vec3::new(0, 0, 1)).unwrap();
+        assert_eq!(Vec3::new(-1, 0, 0), turn.apply(Vec3::new(0, 1, 0)));
+        assert_eq!(Rotation::IDENTITY, turn.then(turn).then(turn).then(turn));
+    }
+
+    #[test]
+    fn transforms() {
+        let rotations = Rotation::all();
+        let a_to_b = Transform::new(rotations[5], Vec3::new(10, -20, 30));
+        let b_to_c = Transform::new(rotations[17], Vec3::new(-4, 5, 6));
+        let v = Vec3::new(7i64, -8, 9);
+
+        assert_eq!(b_to_c.apply(a_to_b.apply(v)), a_to_b.then(&b_to_c).apply(v));
+        assert_eq!(v, a_to_b.inverse().apply(a_to_b.apply(v)));
+        assert_eq!(Transform::identity(), a_to_b.then(&a_to_b.inverse()));
+    }
+}
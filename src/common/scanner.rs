@@ -0,0 +1,230 @@
+use super::{parse_int, Integer};
+use crate::error::{AocError, Result};
+
+/// A cursor over the input bytes for the hand written parsers. It keeps track of the
+/// line and column it's at, so errors can point at where the input went wrong.
+#[derive(Clone, Debug)]
+pub struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    line: usize,
+    line_start: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(bytes: &'a [u8]) -> Scanner<'a> {
+        Scanner { bytes, pos: 0, line: 1, line_start: 0 }
+    }
+
+    /// The byte offset into the input.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// The current line, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The current column, starting at 1.
+    pub fn column(&self) -> usize {
+        self.pos - self.line_start + 1
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    pub fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    /// A parse error at the current position.
+    pub fn error(&self, message: impl Into<String>) -> AocError {
+        AocError::parse(self.line, self.column(), message)
+    }
+
+    fn advance(&mut self, count: usize) {
+        for _ in 0..count {
+            if self.bytes.get(self.pos) == Some(&b'\n') {
+                self.line += 1;
+                self.line_start = self.pos + 1;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn advance_while(&mut self, mut pred: impl FnMut(u8) -> bool) -> &'a [u8] {
+        let start = self.pos;
+        while self.peek().is_some_and(&mut pred) {
+            self.advance(1);
+        }
+        &self.bytes[start..self.pos]
+    }
+
+    /// Skips any whitespace, including line breaks.
+    pub fn skip_ws(&mut self) {
+        self.advance_while(|b| b.is_ascii_whitespace());
+    }
+
+    /// Whether the input continues with the literal, without consuming it.
+    pub fn looking_at(&self, literal: &str) -> bool {
+        self.bytes[self.pos..].starts_with(literal.as_bytes())
+    }
+
+    /// Consumes the literal if the input continues with it.
+    pub fn eat(&mut self, literal: &str) -> bool {
+        let found = self.looking_at(literal);
+        if found {
+            self.advance(literal.len());
+        }
+        found
+    }
+
+    /// Consumes the literal after skipping whitespace, or fails pointing at what's there instead.
+    pub fn expect(&mut self, literal: &str) -> Result<()> {
+        self.skip_ws();
+        if self.eat(literal) {
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected '{}'", literal)))
+        }
+    }
+
+    /// Takes the next run of letters, digits and underscores after skipping whitespace.
+    pub fn take_ident(&mut self) -> Result<&'a str> {
+        self.skip_ws();
+        let ident = self.advance_while(|b| b.is_ascii_alphanumeric() || b == b'_');
+        if ident.is_empty() {
+            return Err(self.error("Expected a name"));
+        }
+        Ok(std::str::from_utf8(ident).expect("ASCII is valid UTF-8"))
+    }
+
+    /// Reads the next number after skipping whitespace, failing if it doesn't fit in a `T`.
+    pub fn next_int<T: Integer>(&mut self) -> Result<T> {
+        self.skip_ws();
+        let (value, end) = parse_int(self.bytes, self.pos)?;
+        self.advance(end - self.pos);
+        Ok(value)
+    }
+
+    pub fn next_i64(&mut self) -> Result<i64> {
+        self.next_int()
+    }
+
+    pub fn next_u64(&mut self) -> Result<u64> {
+        self.next_int()
+    }
+
+    /// Matches the pattern, reading a number for each `{}` in it. Whitespace in the pattern
+    /// matches any amount of whitespace, everything else has to match exactly. The numbers
+    /// have to fit in a `T`. Usually called through [`scan!`](crate::scan).
+    pub fn scan<T: Integer, const N: usize>(&mut self, pattern: &str) -> Result<[T; N]> {
+        let mut pieces = pattern.split("{}");
+        debug_assert_eq!(N + 1, pattern.split("{}").count(), "Pattern '{}' doesn't have {} numbers", pattern, N);
+
+        self.scan_literal(pieces.next().unwrap_or(""))?;
+        let mut values = Vec::with_capacity(N);
+        for piece in pieces {
+            values.push(self.next_int()?);
+            self.scan_literal(piece)?;
+        }
+
+        Ok(values.try_into().unwrap_or_else(|_| unreachable!("Checked the number of placeholders")))
+    }
+
+    fn scan_literal(&mut self, literal: &str) -> Result<()> {
+        for word in literal.split(|c: char| c.is_ascii_whitespace()) {
+            if !word.is_empty() {
+                self.expect(word)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Counts the `{}` placeholders in a [`scan!`](crate::scan) pattern at compile time.
+pub const fn count_placeholders(pattern: &str) -> usize {
+    let bytes = pattern.as_bytes();
+    let mut count = 0;
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        if bytes[i] == b'{' && bytes[i + 1] == b'}' {
+            count += 1;
+            i += 1;
+        }
+        i += 1;
+    }
+    count
+}
+
+/// Scans a line like `scan!(scanner, "on x={}..{},y={}..{}")?` into an array with a number
+/// for each placeholder, see [`Scanner::scan`].
+#[macro_export]
+macro_rules! scan {
+    ($scanner:expr, $pattern:literal) => {{
+        const N: usize = $crate::common::scanner::count_placeholders($pattern);
+        $scanner.scan::<_, N>($pattern)
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_pos(err: AocError) -> (usize, usize) {
+        match err {
+            AocError::Parse { line, column, .. } => (line, column),
+            other => panic!("Unexpected error {}", other),
+        }
+    }
+
+    #[test]
+    fn tokens() {
+        let mut scanner = Scanner::new(b"move -12 -> 0\n  next_one 18446744073709551615");
+        assert_eq!("move", scanner.take_ident().unwrap());
+        assert_eq!(-12, scanner.next_i64().unwrap());
+        scanner.expect("->").unwrap();
+        assert_eq!(0, scanner.next_u64().unwrap());
+        assert_eq!(1, scanner.line());
+
+        assert_eq!("next_one", scanner.take_ident().unwrap());
+        assert_eq!((2, 11), (scanner.line(), scanner.column()));
+        assert_eq!(u64::MAX, scanner.next_u64().unwrap());
+        scanner.skip_ws();
+        assert!(scanner.is_at_end());
+    }
+
+    #[test]
+    fn errors() {
+        let mut scanner = Scanner::new(b"a\n 9223372036854775808 -x");
+        scanner.take_ident().unwrap();
+        assert_eq!((2, 2), error_pos(scanner.next_i64().unwrap_err()));
+        assert_eq!(-9223372036854775808, Scanner::new(b"-9223372036854775808").next_i64().unwrap());
+
+        let mut scanner = Scanner::new(b"-x");
+        assert_eq!((1, 1), error_pos(scanner.next_i64().unwrap_err()));
+        assert_eq!((1, 1), error_pos(scanner.next_u64().unwrap_err()));
+        assert_eq!((1, 1), error_pos(scanner.expect("->").unwrap_err()));
+        assert!(scanner.eat("-"));
+        assert_eq!("x", scanner.take_ident().unwrap());
+        assert_eq!((1, 3), error_pos(scanner.take_ident().unwrap_err()));
+    }
+
+    #[test]
+    fn scan_pattern() {
+        let mut scanner = Scanner::new(b"on x=-20..26,y=-36..17\n0,9 ->  5,9");
+        assert_eq!("on", scanner.take_ident().unwrap());
+        assert_eq!([-20, 26, -36, 17], scan!(scanner, " x={}..{},y={}..{}").unwrap());
+        assert_eq!([0u8, 9, 5, 9], scan!(scanner, "{},{} -> {},{}").unwrap());
+
+        let mut scanner = Scanner::new(b"1,2\n3;4\n300");
+        assert_eq!([1, 2], scan!(scanner, "{},{}").unwrap());
+        assert_eq!((2, 2), error_pos(scan!(scanner, "{},{}").map(|[a, b]: [i32; 2]| a + b).unwrap_err()));
+
+        let mut scanner = Scanner::new(b"300");
+        assert_eq!((1, 1), error_pos(scan!(scanner, "{}").map(|[a]: [u8; 1]| a).unwrap_err()));
+        assert_eq!(0, count_placeholders("no numbers {"));
+    }
+}
@@ -0,0 +1,142 @@
+use super::FastHasher;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::{BuildHasherDefault, Hash};
+use std::ops::Add;
+
+/// A state in a search for the cheapest way to reach a goal.
+pub trait SearchState: Clone + Eq + Hash {
+    type Cost: Copy + Ord + Default + Add<Output = Self::Cost>;
+
+    /// Calls `visit` with every state one move away and the cost of that move.
+    fn neighbors(&self, visit: impl FnMut(Self, Self::Cost));
+
+    fn is_goal(&self) -> bool;
+
+    /// A lower bound on the cost left to reach a goal. It must never overestimate, or the
+    /// path found might not be the cheapest. The default of zero makes this Dijkstra.
+    fn heuristic(&self) -> Self::Cost {
+        Self::Cost::default()
+    }
+}
+
+/// How much work a search did.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// States taken off the queue to look at their neighbors.
+    pub expanded: usize,
+    /// States put on the queue, including ones later found to be reachable for cheaper.
+    pub pushed: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchResult<S: SearchState> {
+    pub cost: S::Cost,
+    /// Every state from the start to the goal, both included.
+    pub path: Vec<S>,
+    pub stats: SearchStats,
+}
+
+/// Finds the cheapest path from the start to a goal with A*, or Dijkstra if the states don't
+/// provide a heuristic. Returns None if no goal can be reached.
+pub fn shortest_path<S: SearchState>(start: S) -> Option<SearchResult<S>> {
+    // States live in `nodes` with the index of the state they were reached from, and `best`
+    // maps each state to its cheapest known cost and node. The queue holds node indices.
+    let mut nodes: Vec<(S, Option<usize>)> = vec![(start.clone(), None)];
+    let mut best: HashMap<S, (S::Cost, usize), BuildHasherDefault<FastHasher>> = HashMap::default();
+    let mut queue = BinaryHeap::new();
+    let mut stats = SearchStats { expanded: 0, pushed: 1 };
+    let mut next_states = Vec::new();
+
+    let start_cost = S::Cost::default();
+    queue.push((Reverse(start_cost + start.heuristic()), Reverse(start_cost), 0));
+    best.insert(start, (start_cost, 0));
+
+    while let Some((_, Reverse(cost), idx)) = queue.pop() {
+        let state = &nodes[idx].0;
+        if best[state].1 != idx {
+            continue; // We already found a cheaper way here.
+        }
+
+        if state.is_goal() {
+            return Some(SearchResult { cost, path: reconstruct_path(&nodes, idx), stats });
+        }
+
+        stats.expanded += 1;
+        state.neighbors(|next, step_cost| next_states.push((next, cost + step_cost)));
+
+        for (next, next_cost) in next_states.drain(..) {
+            let next_idx = nodes.len();
+            match best.entry(next) {
+                Entry::Occupied(mut entry) if entry.get().0 > next_cost => {
+                    entry.insert((next_cost, next_idx));
+                    nodes.push((entry.key().clone(), Some(idx)));
+                },
+                Entry::Occupied(_) => continue,
+                Entry::Vacant(entry) => {
+                    nodes.push((entry.key().clone(), Some(idx)));
+                    entry.insert((next_cost, next_idx));
+                },
+            }
+
+            let priority = next_cost + nodes[next_idx].0.heuristic();
+            queue.push((Reverse(priority), Reverse(next_cost), next_idx));
+            stats.pushed += 1;
+        }
+    }
+
+    None
+}
+
+fn reconstruct_path<S: Clone>(nodes: &[(S, Option<usize>)], mut idx: usize) -> Vec<S> {
+    let mut path = vec![nodes[idx].0.clone()];
+    while let Some(parent) = nodes[idx].1 {
+        path.push(nodes[parent].0.clone());
+        idx = parent;
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counting up from a number to 10 by adding 1 for a cost of 3, or doubling for a cost of 5.
+    #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+    struct Counter(u32, bool);
+
+    impl SearchState for Counter {
+        type Cost = u32;
+
+        fn neighbors(&self, mut visit: impl FnMut(Counter, u32)) {
+            if self.0 < 10 {
+                visit(Counter(self.0 + 1, self.1), 3);
+                visit(Counter(self.0 * 2, self.1), 5);
+            }
+        }
+
+        fn is_goal(&self) -> bool {
+            self.0 == 10
+        }
+
+        fn heuristic(&self) -> u32 {
+            if self.1 { (10 - self.0.min(10)).min(5) } else { 0 }
+        }
+    }
+
+    #[test]
+    fn dijkstra_and_a_star() {
+        let dijkstra = shortest_path(Counter(1, false)).unwrap();
+        assert_eq!(16, dijkstra.cost);
+        assert_eq!(vec![1, 2, 4, 5, 10], dijkstra.path.iter().map(|c| c.0).collect::<Vec<_>>());
+
+        let a_star = shortest_path(Counter(1, true)).unwrap();
+        assert_eq!(16, a_star.cost);
+        assert!(a_star.stats.expanded <= dijkstra.stats.expanded);
+        assert!(a_star.stats.pushed >= a_star.stats.expanded);
+
+        assert!(shortest_path(Counter(11, false)).is_none());
+    }
+}
@@ -0,0 +1,141 @@
+use super::grid::Grid;
+use super::FastHasher;
+use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
+
+/// A grid that only stores the cells that were set, so coordinates can be negative or far apart.
+/// Every other cell reads as the default value.
+#[derive(Clone, Debug)]
+pub struct SparseGrid<T> {
+    cells: HashMap<(i64, i64), T, BuildHasherDefault<FastHasher>>,
+    default: T,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new(default: T) -> SparseGrid<T> {
+        SparseGrid { cells: HashMap::default(), default }
+    }
+
+    /// The value of unset cells.
+    pub fn default_value(&self) -> &T {
+        &self.default
+    }
+
+    pub fn set_default_value(&mut self, default: T) {
+        self.default = default;
+    }
+
+    pub fn get(&self, x: i64, y: i64) -> &T {
+        self.cells.get(&(x, y)).unwrap_or(&self.default)
+    }
+
+    pub fn set(&mut self, x: i64, y: i64, value: T) {
+        self.cells.insert((x, y), value);
+    }
+
+    /// Unsets a cell, so it reads as the default again.
+    pub fn remove(&mut self, x: i64, y: i64) -> Option<T> {
+        self.cells.remove(&(x, y))
+    }
+
+    /// Whether the cell was set, even if it was set to the default value.
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        self.cells.contains_key(&(x, y))
+    }
+
+    /// The number of set cells.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The set cells, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = ((i64, i64), &T)> {
+        self.cells.iter().map(|(pos, value)| (*pos, value))
+    }
+
+    /// The values at each offset in the stencil, unset neighbors reading as the default.
+    pub fn neighbor_values<'a>(&'a self, (x, y): (i64, i64), stencil: &'a [(isize, isize)])
+        -> impl Iterator<Item = &'a T> + 'a
+    {
+        stencil.iter().map(move |(dx, dy)| self.get(x + *dx as i64, y + *dy as i64))
+    }
+
+    /// The inclusive (min, max) corners of the set cells, or None if there are none.
+    pub fn bounds(&self) -> Option<((i64, i64), (i64, i64))> {
+        let mut positions = self.cells.keys();
+        let first = *positions.next()?;
+        Some(positions.fold((first, first), |(min, max), (x, y)| {
+            ((min.0.min(*x), min.1.min(*y)), (max.0.max(*x), max.1.max(*y)))
+        }))
+    }
+}
+
+impl<T: Clone> SparseGrid<T> {
+    /// Sets every cell of the grid, with its top left corner at (0, 0).
+    pub fn from_grid(grid: &Grid<T>, default: T) -> SparseGrid<T> {
+        let mut sparse = SparseGrid::new(default);
+        for ((x, y), value) in grid.enumerate() {
+            sparse.set(x as i64, y as i64, value.clone());
+        }
+        sparse
+    }
+
+    /// Returns the cell for writing, setting it to the default first if needed.
+    pub fn get_mut(&mut self, x: i64, y: i64) -> &mut T {
+        self.cells.entry((x, y)).or_insert_with(|| self.default.clone())
+    }
+
+    /// Copies the inclusive rectangle between the corners into a dense grid.
+    pub fn to_grid_within(&self, (min_x, min_y): (i64, i64), (max_x, max_y): (i64, i64)) -> Grid<T> {
+        let width = (max_x - min_x + 1).max(0) as usize;
+        let height = (max_y - min_y + 1).max(0) as usize;
+        Grid::from_fn(width, height, |x, y| self.get(min_x + x as i64, min_y + y as i64).clone())
+    }
+
+    /// Copies the bounding box of the set cells into a dense grid, e.g. for printing.
+    pub fn to_grid(&self) -> Grid<T> {
+        match self.bounds() {
+            Some((min, max)) => self.to_grid_within(min, max),
+            None => Grid::from_vec(0, 0, Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_and_set() {
+        let mut grid = SparseGrid::new('.');
+        grid.set(-5, 3, '#');
+        grid.set(1_000_000_000_000, -2, '#');
+        *grid.get_mut(0, 0) = 'x';
+
+        assert_eq!('#', *grid.get(-5, 3));
+        assert_eq!('.', *grid.get(-5, 4));
+        assert_eq!(3, grid.len());
+        assert!(grid.contains(0, 0) && !grid.contains(0, 1));
+        assert_eq!(Some((-5, -2)), grid.bounds().map(|(min, _)| min));
+        assert_eq!(Some((1_000_000_000_000, 3)), grid.bounds().map(|(_, max)| max));
+
+        assert_eq!(Some('x'), grid.remove(0, 0));
+        grid.set_default_value('o');
+        assert_eq!('o', *grid.get(0, 0));
+    }
+
+    #[test]
+    fn dense_conversion() {
+        let dense = Grid::parse_chars("#.\n.#\n").unwrap();
+        let mut sparse = SparseGrid::from_grid(&dense, ' ');
+        sparse.set(-1, 0, '#');
+
+        assert_eq!("#.\n.#\n", sparse.to_grid_within((0, 0), (1, 1)).to_string());
+        assert_eq!("##.\n .#\n", sparse.to_grid().to_string());
+        assert_eq!(0, SparseGrid::<char>::new(' ').to_grid().width());
+    }
+}
@@ -0,0 +1,230 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+
+/// The component types the vectors support: any of the integer types, really.
+pub trait Scalar: Copy + Default + Ord + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> {
+    /// The distance between two values, which works for unsigned types too.
+    fn distance(self, other: Self) -> Self {
+        if self > other { self - other } else { other - self }
+    }
+}
+
+impl<T: Copy + Default + Ord + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Scalar for T {}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Vec2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Vec3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Scalar> Vec2<T> {
+    pub fn new(x: T, y: T) -> Vec2<T> {
+        Vec2 { x, y }
+    }
+
+    pub fn zero() -> Vec2<T> {
+        Vec2::default()
+    }
+
+    /// Applies `f` to every component.
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> Vec2<U> {
+        Vec2 { x: f(self.x), y: f(self.y) }
+    }
+
+    /// The component-wise minimum.
+    pub fn min(self, other: Vec2<T>) -> Vec2<T> {
+        Vec2::new(self.x.min(other.x), self.y.min(other.y))
+    }
+
+    /// The component-wise maximum.
+    pub fn max(self, other: Vec2<T>) -> Vec2<T> {
+        Vec2::new(self.x.max(other.x), self.y.max(other.y))
+    }
+
+    pub fn manhattan(self, other: Vec2<T>) -> T {
+        self.x.distance(other.x) + self.y.distance(other.y)
+    }
+
+    /// The distance when diagonal steps count as one, like a king moves on a chess board.
+    pub fn chebyshev(self, other: Vec2<T>) -> T {
+        self.x.distance(other.x).max(self.y.distance(other.y))
+    }
+
+    pub fn dot(self, other: Vec2<T>) -> T {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// The z component of the 3D cross product, positive if `other` is counter-clockwise of `self`.
+    pub fn cross(self, other: Vec2<T>) -> T {
+        self.x * other.y - self.y * other.x
+    }
+}
+
+impl<T: Scalar> Vec3<T> {
+    pub fn new(x: T, y: T, z: T) -> Vec3<T> {
+        Vec3 { x, y, z }
+    }
+
+    pub fn zero() -> Vec3<T> {
+        Vec3::default()
+    }
+
+    /// Applies `f` to every component.
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> Vec3<U> {
+        Vec3 { x: f(self.x), y: f(self.y), z: f(self.z) }
+    }
+
+    /// The component-wise minimum.
+    pub fn min(self, other: Vec3<T>) -> Vec3<T> {
+        Vec3::new(self.x.min(other.x), self.y.min(other.y), self.z.min(other.z))
+    }
+
+    /// The component-wise maximum.
+    pub fn max(self, other: Vec3<T>) -> Vec3<T> {
+        Vec3::new(self.x.max(other.x), self.y.max(other.y), self.z.max(other.z))
+    }
+
+    pub fn manhattan(self, other: Vec3<T>) -> T {
+        self.x.distance(other.x) + self.y.distance(other.y) + self.z.distance(other.z)
+    }
+
+    /// The distance when diagonal steps count as one.
+    pub fn chebyshev(self, other: Vec3<T>) -> T {
+        self.x.distance(other.x).max(self.y.distance(other.y)).max(self.z.distance(other.z))
+    }
+
+    pub fn dot(self, other: Vec3<T>) -> T {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Vec3<T>) -> Vec3<T> {
+        Vec3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+}
+
+/// Implements the operators for a vector type with the given components.
+macro_rules! impl_ops {
+    ($vec:ident, $($field:ident: $idx:literal),+) => {
+        impl<T: Scalar> Add for $vec<T> {
+            type Output = $vec<T>;
+            fn add(self, other: $vec<T>) -> $vec<T> {
+                $vec { $($field: self.$field + other.$field),+ }
+            }
+        }
+
+        impl<T: Scalar> Sub for $vec<T> {
+            type Output = $vec<T>;
+            fn sub(self, other: $vec<T>) -> $vec<T> {
+                $vec { $($field: self.$field - other.$field),+ }
+            }
+        }
+
+        impl<T: Scalar> AddAssign for $vec<T> {
+            fn add_assign(&mut self, other: $vec<T>) {
+                *self = *self + other;
+            }
+        }
+
+        impl<T: Scalar> SubAssign for $vec<T> {
+            fn sub_assign(&mut self, other: $vec<T>) {
+                *self = *self - other;
+            }
+        }
+
+        /// Scales every component.
+        impl<T: Scalar> Mul<T> for $vec<T> {
+            type Output = $vec<T>;
+            fn mul(self, factor: T) -> $vec<T> {
+                $vec { $($field: self.$field * factor),+ }
+            }
+        }
+
+        impl<T: Neg<Output = T>> Neg for $vec<T> {
+            type Output = $vec<T>;
+            fn neg(self) -> $vec<T> {
+                $vec { $($field: -self.$field),+ }
+            }
+        }
+
+        /// The components by axis, starting with x at 0.
+        impl<T> Index<usize> for $vec<T> {
+            type Output = T;
+            fn index(&self, axis: usize) -> &T {
+                match axis {
+                    $($idx => &self.$field,)+
+                    _ => panic!("Axis {} out of range for {}", axis, stringify!($vec)),
+                }
+            }
+        }
+
+        impl<T> IndexMut<usize> for $vec<T> {
+            fn index_mut(&mut self, axis: usize) -> &mut T {
+                match axis {
+                    $($idx => &mut self.$field,)+
+                    _ => panic!("Axis {} out of range for {}", axis, stringify!($vec)),
+                }
+            }
+        }
+    };
+}
+
+impl_ops!(Vec2, x: 0, y: 1);
+impl_ops!(Vec3, x: 0, y: 1, z: 2);
+
+impl<T: fmt::Display> fmt::Display for Vec2<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.x, self.y)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Vec3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.x, self.y, self.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vec2() {
+        let a = Vec2::new(3, -4);
+        let b = Vec2::new(-1, 2);
+        assert_eq!(Vec2::new(2, -2), a + b);
+        assert_eq!(Vec2::new(4, -6), a - b);
+        assert_eq!(Vec2::new(-6, 8), -a * 2);
+        assert_eq!((-1, -4), (a.min(b).x, a.min(b).y));
+        assert_eq!(Vec2::new(3, 2), a.max(b));
+        assert_eq!((10, 6), (a.manhattan(b), a.chebyshev(b)));
+        assert_eq!((-11, 2), (a.dot(b), a.cross(b)));
+        assert_eq!(5u32, Vec2::new(1u32, 7).manhattan(Vec2::new(4, 5)));
+    }
+
+    #[test]
+    fn vec3() {
+        let mut a = Vec3::new(1i64, 2, 3);
+        let b = Vec3::new(4, -5, 6);
+        assert_eq!(Vec3::new(5, -3, 9), a + b);
+        assert_eq!((12, 7), (a.dot(b), a.chebyshev(b)));
+        assert_eq!(Vec3::new(27, 6, -13), a.cross(b));
+        assert_eq!(0, a.cross(b).dot(a));
+        assert_eq!(Vec3::new(1, 2, 3), a.map(i64::abs));
+
+        a[2] = 10;
+        a -= Vec3::new(1, 1, 1);
+        assert_eq!((0, 1, 9), (a[0], a[1], a[2]));
+        assert_eq!("0 1 9", a.to_string());
+    }
+}
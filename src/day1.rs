@@ -1,122 +1,96 @@
 use super::common;
-use std::path::Path;
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 
-fn parse_numbers_forward(input_bytes: &Vec<u8>) -> Vec<u32> {
+fn parse_numbers_forward(input_bytes: &[u8]) -> Result<Vec<u32>> {
     let mut numbers = Vec::new();
+    let mut cursor = 0;
 
-    let mut working_buf = Vec::new();
-    working_buf.reserve(32);
-
-    for character in input_bytes {
+    while let Some(character) = input_bytes.get(cursor) {
         match *character as char {
-            '\n' | '\r' => {
-                if working_buf.is_empty() {
-                    continue;
-                }
-
-                let mut accumulator = 0;
-                let mut position = 1;
-
-                for digit in working_buf.iter().rev() {
-                    accumulator += digit * position;
-                    position *= 10;
-                }
-
-                working_buf.clear();
-                numbers.push(accumulator);
-            },
+            '\n' | '\r' => cursor += 1,
             '0'..='9' => {
-                let digit_val = character - ('0' as u8);
-                working_buf.push(digit_val as u32);
+                let (number, end) = common::parse_int(input_bytes, cursor)?;
+                numbers.push(number);
+                cursor = end;
             },
             _ => {
-                panic!("Found unexpected char \'{}\' in stream.", character);
+                return Err(AocError::parse_at(input_bytes, cursor,
+                    format!("Found unexpected char '{}' in stream.", *character as char)));
             },
         }
     }
 
-    if working_buf.len() > 0 {
-        let mut accumulator = 0;
-        let mut position = 1;
-    
-        for digit in working_buf.iter().rev() {
-            accumulator += digit * position;
-            position *= 10;
-        }    
-
-        numbers.push(accumulator);
-    }
-
-    numbers
+    Ok(numbers)
 }
 
-#[allow(dead_code)]
-fn parse_numbers_reverse(input_bytes: &Vec<u8>) -> Vec<u32> {
-    let mut numbers = Vec::new();
-    
-    let mut accumulator = 0;
-    let mut digit = 1;
-    
-    for character in input_bytes.iter().rev() {
-        match *character as char {
-            '\n' | '\r' => {
-                if digit > 1 { // If we didnt push atleast one digit, we had consecutive whitespace.
-                    numbers.push(accumulator);
-                } 
-                accumulator = 0;
-                digit = 1;
-            },
-            '0'..='9' => {
-                let digit_val = character - ('0' as u8);
-                accumulator += (digit_val as u32) * digit;
-                digit *= 10;
-            },
-            _ => {
-                panic!("Found unexpected char \'{}\' in stream.", character);
-            },
-        }
-    }
-
-    if digit > 1 {
-        numbers.push(accumulator); // The final value wont be terminated by a newline.
-    }
+pub struct Day1;
 
-    numbers.reverse();
+impl Solution for Day1 {
+    type Input = Vec<u32>;
 
-    numbers
-}
-
-pub fn run(root_dir:  &Path) {
-    let input_path = root_dir.join("day1_input.txt");
-    let input_bytes = common::read_input_bytes(input_path.as_path());
+    fn parse(input: &str) -> Result<Vec<u32>> {
+        parse_numbers_forward(input.as_bytes())
+    }
 
-    let samples = parse_numbers_forward(&input_bytes);
+    fn part1(samples: &Vec<u32>) -> Result<Answer> {
+        let number_of_increases = samples.windows(2)
+            .filter(|pair| pair[1] > pair[0])
+            .count();
 
-    let num_count = samples.len();
-    println!("Parsed {} numbers!", num_count);
+        number_of_increases.try_into()
+    }
 
-    let mut number_of_increases = 0;
+    fn part2(samples: &Vec<u32>) -> Result<Answer> {
+        // Three u32 samples can add up to more than a u32 holds.
+        let window_sum = |start: usize| samples[start..start + 3].iter().map(|x| *x as u64).sum::<u64>();
 
-    for i in 1..num_count {
-        if samples[i] > samples[i - 1] {
-            number_of_increases += 1;
+        let mut num_three_sum_increase = 0;
+        for i in 1..samples.len().saturating_sub(2) {
+            if window_sum(i) > window_sum(i - 1) {
+                num_three_sum_increase += 1;
+            }
         }
-    }
 
-    println!("Single depth increased {} times", number_of_increases);
+        Ok(num_three_sum_increase.into())
+    }
+}
 
-    let three_sum_count = ((num_count / 3) * 3) - 3;
-    let mut num_three_sum_increase = 0;
-    for i in 1..three_sum_count {
-        
-        let lhs_idx = i - 1;
-        let lhs_sum = samples[lhs_idx] + samples[lhs_idx + 1] + samples[lhs_idx + 2]; 
-        let rhs_sum = samples[i]       + samples[i + 1]       + samples[i + 2]; 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
+
+    const EXAMPLE: &str = "\
+199
+200
+208
+210
+200
+207
+240
+269
+260
+263
+";
+
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(7), Answer::Int(5)), solve_example::<Day1>(EXAMPLE));
+    }
 
-        if rhs_sum > lhs_sum {
-            num_three_sum_increase += 1;
+    #[test]
+    fn bad_numbers() {
+        match Day1::parse("199\n12345678901234567890\n") {
+            Err(AocError::Parse { line, column, .. }) => assert_eq!((2, 1), (line, column)),
+            _ => panic!("Expected the number to overflow"),
         }
+        assert!(Day1::parse("199\n2x0\n").is_err());
     }
 
-    println!("Sliding window sum increased {} times", num_three_sum_increase);
-}
\ No newline at end of file
+    #[test]
+    fn large_sums() {
+        let samples = Day1::parse("4000000000\n4000000000\n4000000000\n4000000001\n").unwrap();
+        assert_eq!(Answer::Int(1), Day1::part2(&samples).unwrap());
+    }
+}
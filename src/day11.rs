@@ -1,73 +1,115 @@
-use super::common;
-use std::path::Path;
-
-#[allow(unused)]
-fn print_grid(grid: &Vec<u8>, width: usize) {
-    for i in 0..grid.len() {
-        if (i % width) == 0 {
-            print!("\n");
+use super::common::grid::{Edges, Grid, NEIGHBORS_8};
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
+use std::collections::HashSet;
+
+/// Advances the grid by a single step and returns the number of flashes.
+fn step(grid: &mut Grid<u8>) -> u32 {
+    let mut num_flashes = 0;
+
+    for val in grid.iter_mut() {
+        *val += 1;
+    }
+
+    let mut this_step = grid.enumerate()
+        .filter(|(_, val)| **val == 10)
+        .map(|(pos, _)| pos)
+        .collect::<Vec<_>>();
+    let mut next_step = Vec::new();
+
+    while !this_step.is_empty() {
+        for pos in &this_step {
+            grid[*pos] = 0;
+            num_flashes += 1;
+
+            // Not using neighbors8() here, since it would keep the grid borrowed.
+            for offset in NEIGHBORS_8 {
+                let Some(n) = grid.neighbor_pos(*pos, offset, &Edges::Clip) else {
+                    continue;
+                };
+
+                let val = &mut grid[n];
+                if *val > 0 {
+                    *val = u8::min(*val + 1, 11); // avoid overflow.
+
+                    if *val == 10 {
+                        next_step.push(n);
+                    }
+                }
+            }
         }
-        print!("{} ", grid[i]);
+
+        this_step.clear();
+        std::mem::swap(&mut this_step, &mut next_step);
     }
+
+    num_flashes
 }
 
-pub fn run(root_dir: &Path) {
-    let (mut grid, width, height) = {     
-        let input_path = root_dir.join("day11_input.txt");
-        let bytes = common::read_input_bytes(input_path.as_path());
-        common::parse_grid(&bytes)
-    };
+pub struct Day11;
 
-    let mut num_flashes = 0;
-    let mut step = 0;
-    loop {
-        if grid.iter().all(|x| *x == 0) {
-            println!("Sync on step {}", step);
-            break;
-        }
+impl Solution for Day11 {
+    type Input = Grid<u8>;
+
+    fn parse(input: &str) -> Result<Grid<u8>> {
+        Grid::parse_digits(input)
+    }
+
+    fn part1(octopuses: &Grid<u8>) -> Result<Answer> {
+        let mut grid = octopuses.clone();
 
-        for val in &mut grid {
-            *val += 1;
+        let mut num_flashes = 0;
+        for _ in 0..100 {
+            num_flashes += step(&mut grid);
         }
 
-        let mut this_step = Vec::new();
-        let mut next_step = Vec::new();
+        Ok(num_flashes.into())
+    }
+
+    fn part2(octopuses: &Grid<u8>) -> Result<Answer> {
+        let mut grid = octopuses.clone();
 
-        for i in 0..grid.len() {
-            if grid[i] == 10 {
-                this_step.push(i);
+        // Steps only depend on the grid, so seeing one again means they never all flash at once.
+        let mut seen = HashSet::new();
+        let mut num_steps = 0;
+        while !grid.iter().all(|x| *x == 0) {
+            if !seen.insert(grid.iter().copied().collect::<Vec<_>>()) {
+                return Err(AocError::no_solution(format!("The octopuses repeat after {} steps without syncing", num_steps)));
             }
+            step(&mut grid);
+            num_steps += 1;
         }
 
-        while this_step.len() > 0 {
-            for idx in &this_step {
-                grid[*idx] = 0;
-                num_flashes += 1;
-
-                let taps = common::get_box_taps(*idx, width, height);
-                for tap in taps {
-                    match tap {
-                        Some(i) => {
-                            if grid[i] > 0 {
-                                grid[i] += 1;
-                                grid[i] = u8::min(grid[i], 11); // avoid overflow.
-
-                                if grid[i] == 10 {
-                                    next_step.push(i);
-                                }
-                            }    
-                        }
-                        None => (),
-                    }
-                }
-            }
+        Ok(num_steps.into())
+    }
+}
 
-            this_step.clear();
-            std::mem::swap(&mut this_step, &mut next_step);
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
 
-        step += 1;
+    const EXAMPLE: &str = "\
+5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526
+";
+
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(1656), Answer::Int(195)), solve_example::<Day11>(EXAMPLE));
     }
 
-    println!("Num flashes {}", num_flashes);
-}
\ No newline at end of file
+    #[test]
+    fn never_syncing() {
+        let grid = Day11::parse(&EXAMPLE[..11]).unwrap();
+        assert!(matches!(Day11::part2(&grid), Err(AocError::NoSolution { .. })));
+    }
+}
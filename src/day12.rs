@@ -1,186 +1,133 @@
-use super::common;
-use std::path::Path;
-use std::collections::HashMap;
-use std::collections::VecDeque;
-
-struct Node {
-    connections: Vec<usize>,
-    is_large: bool
+use super::common::graph::{Graph, NodeId, RevisitRule};
+use super::common::Scanner;
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
+
+pub struct CaveGraph {
+    graph: Graph,
+    is_large: Vec<bool>,
+    start_id: NodeId,
+    end_id: NodeId,
 }
 
-impl Node {
-    pub fn from_tok(tok: &[u8]) -> Node {
-        let is_large = (tok[0] as char).is_uppercase();
-
-        Node {
-            connections: Vec::new(),
-            is_large
-        }
-    }
+/// Small caves may be visited once, large caves any number of times. With a spare visit,
+/// one small cave other than the start may be visited twice.
+struct CaveRule<'a> {
+    caves: &'a CaveGraph,
+    spare_visit: bool,
 }
 
-fn get_or_add_node_id(tok: &[u8], id_map: &mut HashMap<String, usize>, graph: &mut Vec<Node>) -> usize {
-    let id_str = String::from_iter(tok.iter().map(|x| *x as char));
-
-    match id_map.get(&id_str) {
-        Some(id) => {
-            return *id;
-        },
-        None => {
-            let id = graph.len();
-            id_map.insert(id_str, id);
-
-            let node = Node::from_tok(&tok);
-            graph.push(node);
-
-            return id;
-        },
+impl RevisitRule for CaveRule<'_> {
+    fn allows(&self, node: NodeId, visits: usize) -> bool {
+        visits == 0 || self.caves.is_large[node] || (self.spare_visit && visits == 1 && node != self.caves.start_id)
     }
-}
 
-fn tok_eq(lhs: &[u8], rhs: &[char]) -> bool {
-    if lhs.len() != rhs.len() {
-        return false;
+    fn enter(&mut self, node: NodeId, visits: usize) {
+        if visits > 0 && !self.caves.is_large[node] {
+            self.spare_visit = false;
+        }
     }
 
-    for i in 0..lhs.len() {
-        if lhs[i] as char != rhs[i] {
-            return false;
+    fn leave(&mut self, node: NodeId, visits: usize) {
+        if visits > 0 && !self.caves.is_large[node] {
+            self.spare_visit = true;
         }
     }
-
-    true
 }
 
-fn is_start_node(tok: &[u8]) -> bool {
-    let start_id = ['s', 't', 'a', 'r', 't'];
-    tok_eq(&tok, &start_id)
+fn count_paths(caves: &CaveGraph, spare_visit: bool) -> usize {
+    caves.graph.count_paths(caves.start_id, caves.end_id, &mut CaveRule { caves, spare_visit })
 }
 
-fn is_end_node(tok: &[u8]) -> bool {
-    let end_id = ['e', 'n', 'd'];
-    tok_eq(&tok, &end_id)
+fn is_large_cave(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
 }
 
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day12_input.txt");
-    let bytes = common::read_input_bytes(input_path.as_path());
-
-    let mut ids = Vec::new();
-
-    let (graph, start_id, end_id) = {
-        let mut graph: Vec<Node> = Vec::new();
-
-        let mut tok_to_id: HashMap<String, usize> = HashMap::new();
-        let mut tok_buf = Vec::new();
-
-        let mut start_id: Option<usize> = None;
-        let mut end_id: Option<usize> = None;
-
-        let mut cursor = 0;
-        loop {
-            while !(bytes[cursor] as char).is_ascii_alphabetic() {
-                cursor += 1;
-            }
-
-            while (bytes[cursor] as char).is_ascii_alphabetic() {
-                tok_buf.push(bytes[cursor]);
-                cursor += 1;
-            }
-
-            let from_node = get_or_add_node_id(&tok_buf, &mut tok_to_id, &mut graph);
-            if is_start_node(&tok_buf) {
-                start_id = Some(from_node);
-            } else if is_end_node(&tok_buf) {
-                end_id = Some(from_node);
-            }
-
-            tok_buf.clear();
-            cursor += 1;
-
-            while cursor < bytes.len() &&
-                  (bytes[cursor] as char).is_ascii_alphabetic() {
-                tok_buf.push(bytes[cursor]);
-                cursor += 1;
-            }
+fn parse_graph(bytes: &[u8]) -> Result<CaveGraph> {
+    let mut graph = Graph::new();
+    let mut scanner = Scanner::new(bytes);
 
-            let to_node = get_or_add_node_id(&tok_buf, &mut tok_to_id, &mut graph);
-            if is_start_node(&tok_buf) {
-                start_id = Some(to_node);
-            } else if is_end_node(&tok_buf) {
-                end_id = Some(to_node);
-            }
+    scanner.skip_ws();
+    while !scanner.is_at_end() {
+        let line = scanner.line();
+        let from = scanner.take_ident()?;
+        scanner.expect("-")?;
+        let to = scanner.take_ident()?;
 
-            tok_buf.clear();
-
-            graph[from_node].connections.push(to_node);
-            graph[to_node].connections.push(from_node);
-
-            if cursor >= bytes.len() {
-                break;
-            }
+        // Paths could go back and forth between two large caves forever.
+        if is_large_cave(from) && is_large_cave(to) {
+            return Err(AocError::parse(line, 1, format!("Large caves {} and {} are connected", from, to)));
         }
 
-        ids.resize(graph.len(), String::default());
-        for kvp in &tok_to_id {
-            ids[*kvp.1] = kvp.0.clone();
-        }
+        let (from, to) = (graph.intern(from), graph.intern(to));
+        graph.add_undirected_edge(from, to, ());
+        scanner.skip_ws();
+    }
 
-        (graph, start_id.unwrap(), end_id.unwrap())
+    let (Some(start_id), Some(end_id)) = (graph.id("start"), graph.id("end")) else {
+        return Err(AocError::parse_at(bytes, bytes.len(), "Expected both a start and an end cave"));
     };
 
-    let mut num_paths = 0;
-
-    let mut closed: Vec<bool> = vec![false; graph.len()];
-    closed[start_id] = true;
-
-    // The current node and how many neighbors we need to visit
-    let mut queue = VecDeque::new();
-    queue.push_front((start_id, graph[start_id].connections.len()));
-
-    let mut path = Vec::new();
-    path.push(start_id);
-
-    let mut small_seal: Option<usize> = None;
-
-    while !queue.is_empty() {
-        let top_el = queue.front_mut().unwrap();
-        let cur_node_id = top_el.0;
-
-        if (cur_node_id == end_id) || (top_el.1 == 0) {
-            if cur_node_id == end_id {
-                num_paths += 1;
-            }
+    let is_large = graph.nodes().map(|id| is_large_cave(graph.name(id))).collect();
+    Ok(CaveGraph { graph, is_large, start_id, end_id })
+}
 
-            if small_seal == Some(queue.len()) {
-                small_seal = None;
-            } else {
-                closed[cur_node_id] = false;
-            }
+pub struct Day12;
 
-            path.pop();
-            queue.pop_front();
-        } else {
-            let next_node_id = graph[cur_node_id].connections[top_el.1 - 1];
-            let next_node = &graph[next_node_id];
-            (*top_el).1 -= 1;
+impl Solution for Day12 {
+    type Input = CaveGraph;
 
-            if next_node_id == start_id {
-                continue;
-            }
+    fn parse(input: &str) -> Result<CaveGraph> {
+        parse_graph(input.as_bytes())
+    }
 
-            if !closed[next_node_id] || next_node.is_large || small_seal.is_none() {
-                queue.push_front((next_node_id, next_node.connections.len()));
+    fn part1(caves: &CaveGraph) -> Result<Answer> {
+        count_paths(caves, false).try_into()
+    }
 
-                if !next_node.is_large && small_seal.is_none() && closed[next_node_id] {
-                    small_seal = Some(queue.len())
-                }
+    fn part2(caves: &CaveGraph) -> Result<Answer> {
+        count_paths(caves, true).try_into()
+    }
+}
 
-                closed[next_node_id] = true;
-                path.push(next_node_id);
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
+
+    const SMALL_EXAMPLE: &str = "\
+start-A
+start-b
+A-c
+A-b
+b-d
+A-end
+b-end
+";
+
+    const LARGER_EXAMPLE: &str = "\
+dc-end
+HN-start
+start-kj
+dc-start
+dc-HN
+LN-dc
+HN-end
+kj-sj
+kj-HN
+kj-dc
+";
+
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(10), Answer::Int(36)), solve_example::<Day12>(SMALL_EXAMPLE));
+        assert_eq!((Answer::Int(19), Answer::Int(103)), solve_example::<Day12>(LARGER_EXAMPLE));
     }
 
-    println!("Num paths {}", num_paths);
-}
\ No newline at end of file
+    #[test]
+    fn connected_large_caves() {
+        let err = Day12::parse("start-A\nA-B\nB-end\n").err().unwrap();
+        assert!(matches!(err, AocError::Parse { line: 2, column: 1, .. }), "{}", err);
+        assert!(Day12::parse("start-A\nA-A\nA-end\n").is_err());
+    }
+}
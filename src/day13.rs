@@ -1,62 +1,54 @@
-use super::common;
-use std::path::Path;
-use std::collections::HashSet;
+use super::common::{self, ocr, Grid, SparseGrid};
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-struct Point2D {
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Point2D {
     x: u32,
     y: u32
 }
 
 #[derive(PartialEq)]
-enum Direction {
+pub enum Direction {
     X, Y
 }
 
-struct Fold {
+pub struct Fold {
     dir: Direction,
     val: u32
 }
 
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day13_input.txt");
-    let bytes = common::read_input_bytes(input_path.as_path());
+pub struct Manual {
+    points: Vec<Point2D>,
+    folds: Vec<Fold>,
+}
 
+fn parse_manual(bytes: &[u8]) -> Result<Manual> {
     let (points, folds) = {
         let mut points: Vec<Point2D> = Vec::new();
 
-        let mut token_buf: Vec<u8> = Vec::new();
-        token_buf.reserve(8);
-
         let mut cursor = 0;    
-        loop {
-            let ix = match common::parse_num(&bytes, &mut token_buf, cursor) {
-                (Some(val), new_cursor) => {
-                    cursor = new_cursor;
-                    val
-                },
-                (None, _) => panic!(),
-            };
+        while cursor < bytes.len() && bytes[cursor].is_ascii_whitespace() {
+            cursor += 1;
+        }
 
+        while cursor < bytes.len() && bytes[cursor].is_ascii_digit() {
+            let (ix, new_cursor) = common::parse_int::<u32>(bytes, cursor)?;
+            cursor = new_cursor;
+
+            if bytes.get(cursor) != Some(&b',') {
+                return Err(AocError::parse_at(bytes, cursor, "Expected ',' between coordinates"));
+            }
             cursor += 1;
 
-            let iy = match common::parse_num(&bytes, &mut token_buf, cursor) {
-                (Some(val), new_cursor) => {
-                    cursor = new_cursor;
-                    val
-                },
-                (None, _) => panic!(),
-            };
+            let (iy, new_cursor) = common::parse_int::<u32>(bytes, cursor)?;
+            cursor = new_cursor;
 
-            points.push(Point2D {x: ix as u32, y: iy as u32});
+            points.push(Point2D {x: ix, y: iy});
 
-            while !bytes[cursor].is_ascii_alphanumeric() {
+            while cursor < bytes.len() && !bytes[cursor].is_ascii_alphanumeric() {
                 cursor += 1;
             }
-
-            if bytes[cursor].is_ascii_alphabetic() {
-                break;
-            }
         }
 
         let mut folds: Vec<Fold> = Vec::new();
@@ -78,84 +70,125 @@ pub fn run(root_dir: &Path) {
                 Direction::Y
             };
 
+            if bytes.get(cursor + 1) != Some(&b'=') {
+                return Err(AocError::parse_at(bytes, cursor + 1, "Expected '=' after the fold axis"));
+            }
             cursor += 2; // skip "x="
 
-            let ival = match common::parse_num(&bytes, &mut token_buf, cursor) {
-                (Some(val), new_cursor) => {
-                    cursor = new_cursor;
-                    val
-                },
-                (None, _) => panic!(),
-            };
+            let (ival, new_cursor) = common::parse_int::<u32>(bytes, cursor)?;
+            cursor = new_cursor;
+
+            folds.push(Fold { dir, val: ival });
+        }
 
-            folds.push(Fold { dir, val: ival as u32 });
+        if folds.is_empty() {
+            return Err(AocError::parse_at(bytes, cursor, "Expected at least one fold"));
         }
 
         (points, folds)
     };
 
-    let mut folded_points: HashSet<Point2D> = HashSet::new();
-    folded_points.reserve(points.len());
-    for point in &points {
-        folded_points.insert(*point);
+    Ok(Manual { points, folds })
+}
+
+/// Folding along a line past the middle of the paper would put dots at negative positions,
+/// which isn't something a puzzle asks for, so that's reported as having no solution.
+fn fold_points(points: &[Point2D], folds: &[Fold]) -> Result<SparseGrid<bool>> {
+    let mut paper = SparseGrid::new(false);
+    for point in points {
+        paper.set(point.x as i64, point.y as i64, true);
     }
 
-    for fold in &folds {
-        let mut new_points: HashSet<Point2D> = HashSet::new();
-        new_points.reserve(folded_points.len());
-
-        if fold.dir == Direction::X {
-            for mut point in folded_points {
-                if point.x > fold.val {
-                    point.x = fold.val - (point.x - fold.val);
-                }
-        
-                new_points.insert(point);
-            }
-        } else {
-            for mut point in folded_points {
-                if point.y > fold.val {
-                    point.y = fold.val - (point.y - fold.val);
-                }
-        
-                new_points.insert(point);
+    for fold in folds {
+        let mut folded = SparseGrid::new(false);
+        let line = fold.val as i64;
+
+        for ((x, y), _) in paper.iter() {
+            let (x, y) = match fold.dir {
+                Direction::X if x > line => (line - (x - line), y),
+                Direction::Y if y > line => (x, line - (y - line)),
+                _ => (x, y),
+            };
+            if x < 0 || y < 0 {
+                let axis = if fold.dir == Direction::X { 'x' } else { 'y' };
+                return Err(AocError::no_solution(format!("Folding along {}={} moves dots off the paper", axis, line)));
             }
+            folded.set(x, y, true);
         }
 
-        folded_points = new_points;
+        paper = folded;
     }
 
-    let mut width = u32::MIN;
-    let mut height = u32::MIN;
+    Ok(paper)
+}
 
-    for point in &folded_points {
-        width = u32::max(width, point.x);
-        height = u32::max(height, point.y);
-    }
+/// The dots on a grid from the origin, just large enough to fit them all.
+fn paper_pixels(paper: &SparseGrid<bool>) -> Grid<bool> {
+    let (_, max) = paper.bounds().unwrap_or_default();
+    paper.to_grid_within((0, 0), max)
+}
 
-    width += 1; // Bump from max
-    height += 1;
+pub struct Day13;
 
-    let mut grid = Vec::new();
-    let grid_len = (width as usize) * (height as usize); 
-    grid.resize(grid_len, false);
+impl Solution for Day13 {
+    type Input = Manual;
 
-    for point in &folded_points {
-        let idx = point.x + (point.y * width);
-        grid[idx as usize] = true;
+    fn parse(input: &str) -> Result<Manual> {
+        parse_manual(input.as_bytes())
     }
 
-    for i in 0..grid.len() {
-        if (i % width as usize) == 0 {
-            print!("\n");            
-        }
+    fn part1(manual: &Manual) -> Result<Answer> {
+        fold_points(&manual.points, &manual.folds[..1])?.len().try_into()
+    }
 
-        if grid[i] {
-            print!("*");
-        } else {
-            print!(" ");
-        }
+    fn part2(manual: &Manual) -> Result<Answer> {
+        // The dots spell out letters.
+        let folded_points = fold_points(&manual.points, &manual.folds)?;
+        Ok(ocr::read_text(&paper_pixels(&folded_points))?.into())
     }
+}
 
-    println!("\n");
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+6,10
+0,14
+9,10
+0,3
+10,4
+4,11
+6,0
+6,12
+4,1
+0,13
+10,12
+3,4
+3,0
+8,4
+1,10
+2,14
+8,10
+9,0
+
+fold along y=7
+fold along x=5
+";
+
+    #[test]
+    fn example() {
+        let manual = Day13::parse(EXAMPLE).unwrap();
+        assert_eq!(Answer::Int(17), Day13::part1(&manual).unwrap());
+
+        // The example folds into a square rather than letters.
+        let pixels = paper_pixels(&fold_points(&manual.points, &manual.folds).unwrap());
+        assert_eq!("#####\n#...#\n#...#\n#...#\n#####\n", pixels.map(|dot| if *dot { '#' } else { '.' }).to_string());
+        assert!(Day13::part2(&manual).is_err());
+    }
+    #[test]
+    fn fold_past_middle() {
+        let manual = Day13::parse("10,0\n0,0\n\nfold along x=2\n").unwrap();
+        assert!(matches!(Day13::part1(&manual), Err(AocError::NoSolution { .. })));
+    }
+}
@@ -1,72 +1,16 @@
-use super::common;
-use std::path::Path;
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 use std::collections::HashMap;
 
 #[derive(Default, Clone)]
-struct Rule {
+pub struct Rule {
     from: String,
     into: [String; 2],
     into_char: char,
 }
 
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day14_input.txt");
-    let bytes = common::read_input_bytes(input_path.as_path());
-
-    let (template, rules) = {
-        let template = bytes.iter()
-            .take_while(|b| (**b).is_ascii_alphabetic())
-            .map(|b| *b as char)
-            .collect::<Vec<_>>();
-
-        let mut cursor = 0;
-
-        // Scan past template string and initial whitespace
-        while bytes[cursor].is_ascii_alphabetic() {
-            cursor += 1;
-        }
-        while !bytes[cursor].is_ascii_alphabetic() {
-            cursor += 1;
-        }
-
-        let mut rules = Vec::new();
-
-        loop {
-            let mut rule = Rule::default();
-            let from_0 = bytes[cursor] as char;
-            cursor += 1;
-            let from_1 = bytes[cursor] as char;
-            cursor += 1;
-
-            while !bytes[cursor].is_ascii_alphabetic() {
-                cursor += 1;
-            }
-
-            let into = bytes[cursor] as char;
-            cursor += 1;
-
-            rule.from.push(from_0);
-            rule.from.push(from_1);
-            rule.into[0].push(from_0);
-            rule.into[0].push(into);
-            rule.into[1].push(into);
-            rule.into[1].push(from_1);
-            rule.into_char = into;
-
-            rules.push(rule);
-
-            while cursor < bytes.len() && !bytes[cursor].is_ascii_alphabetic() {
-                cursor += 1;
-            }
-
-            if cursor >= bytes.len() {
-                break;
-            }
-        }
-
-        (template, rules)
-    };  
-
+/// Returns the (min, max) element counts after running num_iterations of insertions.
+fn polymerize(template: &[char], rules: &[Rule], num_iterations: usize) -> (usize, usize) {
     let mut pair_map: HashMap<String, usize> = HashMap::new();
     let mut char_counts: HashMap<char, usize> = HashMap::new();
 
@@ -81,7 +25,7 @@ pub fn run(root_dir: &Path) {
         }
     }
 
-    for character in &template {
+    for character in template {
         match char_counts.get_mut(character) {
             Some(count) => *count += 1,
             None => {
@@ -94,9 +38,8 @@ pub fn run(root_dir: &Path) {
     enum MutType { Add, Sub }
     let mut mutations: Vec<(&String, usize, MutType)> = Vec::new();
 
-    let num_iterations = 40;
     for _ in 0..num_iterations {
-        for rule in &rules {
+        for rule in rules {
             let found_count = match pair_map.get_mut(&rule.from) {
                 Some(0) => {
                     None
@@ -132,9 +75,8 @@ pub fn run(root_dir: &Path) {
                     }
                 }
             } else {
-                match pair_map.get_mut(mutation.0) {
-                    Some(count) => *count -= mutation.1,
-                    None => (),
+                if let Some(count) = pair_map.get_mut(mutation.0) {
+                    *count -= mutation.1;
                 }
             }
         }
@@ -142,8 +84,6 @@ pub fn run(root_dir: &Path) {
         mutations.clear();
     }
 
-    // println!("{:?}", pair_map);
-
     let mut min_count = usize::MAX;
     let mut max_count = usize::MIN;
 
@@ -152,5 +92,127 @@ pub fn run(root_dir: &Path) {
         max_count = usize::max(*kvp.1, max_count);
     }
 
-    println!("Min {} Max {} Diff {}", min_count, max_count, max_count - min_count);
-}
\ No newline at end of file
+    (min_count, max_count)
+}
+
+pub struct Polymer {
+    template: Vec<char>,
+    rules: Vec<Rule>,
+}
+
+fn element_at(bytes: &[u8], cursor: usize) -> Result<char> {
+    match bytes.get(cursor) {
+        Some(b) if b.is_ascii_alphabetic() => Ok(*b as char),
+        _ => Err(AocError::parse_at(bytes, cursor, "Expected an element")),
+    }
+}
+
+fn parse_rules(bytes: &[u8]) -> Result<Polymer> {
+    let (template, rules) = {
+        let template = bytes.iter()
+            .take_while(|b| (**b).is_ascii_alphabetic())
+            .map(|b| *b as char)
+            .collect::<Vec<_>>();
+
+        let mut cursor = 0;
+
+        if template.is_empty() {
+            return Err(AocError::parse(1, 1, "Expected a polymer template"));
+        }
+
+        // Scan past template string and initial whitespace
+        while cursor < bytes.len() && bytes[cursor].is_ascii_alphabetic() {
+            cursor += 1;
+        }
+        while cursor < bytes.len() && !bytes[cursor].is_ascii_alphabetic() {
+            cursor += 1;
+        }
+
+        let mut rules = Vec::new();
+
+        while cursor < bytes.len() {
+            let mut rule = Rule::default();
+            let from_0 = element_at(bytes, cursor)?;
+            cursor += 1;
+            let from_1 = element_at(bytes, cursor)?;
+            cursor += 1;
+
+            while cursor < bytes.len() && !bytes[cursor].is_ascii_alphabetic() {
+                cursor += 1;
+            }
+
+            let into = element_at(bytes, cursor)?;
+            cursor += 1;
+
+            rule.from.push(from_0);
+            rule.from.push(from_1);
+            rule.into[0].push(from_0);
+            rule.into[0].push(into);
+            rule.into[1].push(into);
+            rule.into[1].push(from_1);
+            rule.into_char = into;
+
+            rules.push(rule);
+
+            while cursor < bytes.len() && !bytes[cursor].is_ascii_alphabetic() {
+                cursor += 1;
+            }
+        }
+
+        (template, rules)
+    };
+
+    Ok(Polymer { template, rules })
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = Polymer;
+
+    fn parse(input: &str) -> Result<Polymer> {
+        parse_rules(input.as_bytes())
+    }
+
+    fn part1(polymer: &Polymer) -> Result<Answer> {
+        let (min_count, max_count) = polymerize(&polymer.template, &polymer.rules, 10);
+        (max_count - min_count).try_into()
+    }
+
+    fn part2(polymer: &Polymer) -> Result<Answer> {
+        let (min_count, max_count) = polymerize(&polymer.template, &polymer.rules, 40);
+        (max_count - min_count).try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
+
+    const EXAMPLE: &str = "\
+NNCB
+
+CH -> B
+HH -> N
+CB -> H
+NH -> C
+HB -> C
+HC -> B
+HN -> C
+NN -> C
+BH -> H
+NC -> B
+NB -> B
+BN -> B
+BB -> N
+BC -> B
+CC -> N
+CN -> C
+";
+
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(1588), Answer::Int(2188189693529)), solve_example::<Day14>(EXAMPLE));
+    }
+}
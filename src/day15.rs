@@ -1,111 +1,100 @@
-use super::common;
-use std::path::Path;
-use std::collections::BinaryHeap;
-
-#[derive(Copy, Clone, Default, Eq, PartialEq)]
-struct Step {
-    cost: usize,
-    node: usize,
+use super::common::search::{shortest_path, SearchState};
+use super::common::Grid;
+use super::error::Result;
+use super::solution::{Answer, Solution};
+use std::hash::{Hash, Hasher};
+
+/// A position on the risk map. Only the position takes part in comparisons.
+#[derive(Clone, Copy)]
+struct Position<'a> {
+    grid: &'a Grid<u8>,
+    pos: (usize, usize),
 }
 
-impl Step {
-    pub fn new(node: usize, cost: usize) -> Step {
-        Step {cost, node}
+impl PartialEq for Position<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.pos == other.pos
     }
 }
 
-impl Ord for Step {
-    fn cmp(&self, other: &Step) -> std::cmp::Ordering {
-        other.cost.cmp(&self.cost) // Flip the order of comparison so we 
-            .then_with(|| self.node.cmp(&other.node)) // get a min-sorted heap.
+impl Eq for Position<'_> {}
+
+impl Hash for Position<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pos.hash(state);
     }
 }
 
-impl PartialOrd for Step {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+impl SearchState for Position<'_> {
+    type Cost = usize;
+
+    fn neighbors(&self, mut visit: impl FnMut(Self, usize)) {
+        for next in self.grid.neighbors4(self.pos) {
+            visit(Position { grid: self.grid, pos: next }, self.grid[next] as usize);
+        }
+    }
+
+    fn is_goal(&self) -> bool {
+        self.pos == (self.grid.width() - 1, self.grid.height() - 1)
     }
 }
 
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day15_input.txt");
-    let bytes = common::read_input_bytes(input_path.as_path());
-    
-    let (grid, width, height) = {
-        let (grid, width, height) = common::parse_grid(&bytes);
-        let mut full_grid = Vec::new();
-        full_grid.reserve(grid.len() * 25);
-
-        for outer_iter in 0..5 {
-            for y in 0..height {
-                for inner_iter in 0..5 {
-                    for x in 0..width {
-                        let idx = common::get_grid_idx(x, y, width);
-                        let mut val = grid[idx] + inner_iter + outer_iter;
-                        if val > 9 { val = val - 9 }
-                        full_grid.push(val);
-                    }
-                }
-            }
-        }
+/// Tiles the grid 5 times in each direction, incrementing risk with each tile.
+fn expand_grid(grid: &Grid<u8>) -> Grid<u8> {
+    let (width, height) = (grid.width(), grid.height());
 
-        (full_grid, width * 5, height * 5)
-    };
+    Grid::from_fn(width * 5, height * 5, |x, y| {
+        let tile = (x / width + y / height) as u8;
+        let mut val = grid[(x % width, y % height)] + tile;
+        if val > 9 { val -= 9 }
+        val
+    })
+}
 
-    fn get_cost(node: usize, graph: &Vec<u8>) -> usize {
-        graph[node] as usize
-    }
-    
-    let start_node = 0;
-    let end_node = grid.len() - 1;
-
-    let mut queue = BinaryHeap::new();
-    let mut prev_nodes: Vec<Option<usize>> = vec![None; grid.len()];
-    let mut node_costs = vec![usize::MAX; grid.len()];
-    
-    queue.push(Step::new(start_node, 0));
-    prev_nodes[start_node] = None;
-    node_costs[start_node] = get_cost(start_node, &grid);
-
-    while !queue.is_empty() {
-        let current_step = queue.pop().unwrap();
-        let current_node = current_step.node;
-
-        let neighbors = common::get_cross_taps(current_node, width, height);
-        for next in neighbors {
-            if next.is_none() {
-                continue;
-            }
-
-            let next_node = next.unwrap();
-            let new_cost = node_costs[current_node] + get_cost(next_node, &grid);
-            
-            if (node_costs[next_node] == usize::MAX) || (new_cost < node_costs[next_node]) {
-                node_costs[next_node] = new_cost;
-                prev_nodes[next_node] = Some(current_node);
-                queue.push(Step::new(next_node, new_cost));
-            }  
-        }
-    }
+/// Returns the cost of the cheapest path from the top left to the bottom right.
+/// The start is never entered, so it doesn't count towards the total.
+fn find_lowest_risk(grid: &Grid<u8>) -> usize {
+    shortest_path(Position { grid, pos: (0, 0) }).expect("Grids are connected").cost
+}
 
-    let mut path = Vec::new();
-    let mut found_node = end_node;
-    loop {
-        path.push((get_cost(found_node, &grid), common::get_grid_xy(found_node, width, height)));
+pub struct Day15;
 
-        match prev_nodes[found_node] {
-            Some(node) => found_node = node,
-            None => break,
-        }
+impl Solution for Day15 {
+    type Input = Grid<u8>;
+
+    fn parse(input: &str) -> Result<Grid<u8>> {
+        Grid::parse_digits(input)
     }
 
-    path.reverse();
+    fn part1(map: &Grid<u8>) -> Result<Answer> {
+        find_lowest_risk(map).try_into()
+    }
 
-    let mut total: usize = 0;
-    for el in &path[1..(path.len())] { // Exclude end from cost since we dont leave it.
-        // print!("({}, {}, c={}), ", el.1.0, el.1.1, el.0);
-        total += el.0;
+    fn part2(map: &Grid<u8>) -> Result<Answer> {
+        find_lowest_risk(&expand_grid(map)).try_into()
     }
+}
 
-    println!("Total path cost: {}", total);
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
+
+    const EXAMPLE: &str = "\
+1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581
+";
+
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(40), Answer::Int(315)), solve_example::<Day15>(EXAMPLE));
+    }
+}
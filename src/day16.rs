@@ -1,391 +1,239 @@
-use super::common;
-use std::path::Path;
-
-fn decode_byte(byte: u8) -> u8 {
-    if byte <= 57 {
-        byte - 48 // '0' -> 48
-    } else {
-        byte - 55 // 'A' -> 65
-    }
+use super::common::bitstream::{decode_hex, BitOrder, BitReader};
+use super::error::Result;
+use super::solution::{Answer, Solution};
+
+const PACKET_KIND_LITERAL: u64 = 4;
+const LEN_KIND_TOTAL_BITS: u64 = 0;
+const OP_KIND_ADD: u64 = 0;
+const OP_KIND_MUL: u64 = 1;
+const OP_KIND_MIN: u64 = 2;
+const OP_KIND_MAX: u64 = 3;
+const OP_KIND_GT: u64  = 5;
+const OP_KIND_LT: u64  = 6;
+const OP_KIND_EQ: u64  = 7;
+
+/// Errors for malformed packets point at the start of the packet.
+fn apply_op(stream: &BitReader, kind: u64, args: &[u64], packet_pos: usize) -> Result<u64> {
+    if args.is_empty() {
+        return Err(stream.error_at(packet_pos, "Operator packet without subpackets"));
+    }
+
+    let is_comparison = matches!(kind, OP_KIND_GT | OP_KIND_LT | OP_KIND_EQ);
+    if is_comparison && args.len() != 2 {
+        return Err(stream.error_at(packet_pos, "Comparison packet needs exactly two subpackets"));
+    }
+
+    let result = match kind {
+        OP_KIND_ADD => {
+            args.iter().try_fold(0u64, |acc, arg| acc.checked_add(*arg))
+                .ok_or_else(|| stream.error_at(packet_pos, "Sum doesn't fit in 64 bits"))?
+        },
+        OP_KIND_MUL => {
+            args.iter().try_fold(1u64, |acc, arg| acc.checked_mul(*arg))
+                .ok_or_else(|| stream.error_at(packet_pos, "Product doesn't fit in 64 bits"))?
+        },
+        OP_KIND_MIN => {
+            *args.iter().min().unwrap()
+        },
+        OP_KIND_MAX => {
+            *args.iter().max().unwrap()
+        },
+        OP_KIND_GT  => { 
+            if args[0] > args[1] { 1 } else { 0 } 
+        },
+        OP_KIND_LT  => { 
+            if args[0] < args[1] { 1 } else { 0 } 
+        },
+        OP_KIND_EQ  => { 
+            if args[0] == args[1] { 1 } else { 0 } 
+        },
+        _ => return Err(stream.error_at(packet_pos, format!("Unknown packet type {}", kind))),
+    };
+
+    Ok(result)
 }
 
-struct BitStream {
-    bits: Vec<u8>,
-    len: usize,
-    pos: usize
-}
+fn parse_literal_packet(stream: &mut BitReader) -> Result<u64> {
+    let mut result: u64 = 0;
+    loop {
+        let keep_going = stream.read_bit()?;
+        if result >> 60 != 0 {
+            return Err(stream.error("Literal doesn't fit in 64 bits"));
+        }
+        result = (result << 4) | stream.read(4)?;
 
-impl BitStream {
-    pub fn new() -> BitStream {
-        BitStream { bits: Vec::new(), len: 0, pos: 0 }
+        if !keep_going {
+            return Ok(result);
+        }
     }
+}
 
-    pub fn push(&mut self, byte: u8) {
-        self.bits.push(byte);
-        self.len += 8;
-    }
+/// Returns the value the packet evaluates to, and adds the versions
+/// of it and all its subpackets to version_sum.
+fn parse_packet(stream: &mut BitReader, version_sum: &mut u64) -> Result<u64> {
+    let packet_pos = stream.pos();
 
-    pub fn seek(&mut self, new_pos: usize) {
-        assert!(new_pos < self.len);
-        self.pos = new_pos;
-    }
+    let version = stream.read(3)?;
+    let kind = stream.read(3)?;
+    *version_sum += version;
 
-    pub fn try_seek(&mut self, new_pos: usize) -> Result<(), ()> {
-        if new_pos < self.len {
-            self.pos = new_pos;
-            Ok(())
-        } else {
-            Err(())
-        }
+    if kind == PACKET_KIND_LITERAL {
+        return parse_literal_packet(stream);
     }
 
-    pub fn advance(&mut self, by: usize) {
-        assert!(self.pos + by < self.len);
-        self.pos += by;
-    }
+    // PACKET_KIND_OPERATOR
+    let mut args = Vec::new();
+    if stream.read(1)? == LEN_KIND_TOTAL_BITS {
+        let num_bits = stream.read(15)? as usize;
+        let end_pos = stream.pos() + num_bits;
 
-    /// Reads num_bits from the stream currents pos into the returned value.
-    /// NOTE: Reading does not advance the current stream position!
-    pub fn read(&self, num_bits: usize) -> u64 {
-        assert!((self.pos + 1) >= num_bits);    
-    
-        let mut result = 0;
-
-        for i in 0..num_bits {
-            let byte_idx = self.pos - i;
-            let this_byte = {
-                let aligned_idx = byte_idx / 8;
-                self.bits[aligned_idx]
-            };
-            let bit_idx = byte_idx % 8;
-            let ith_bit = {
-                let shift_by = 7 - bit_idx;
-                (this_byte & (1 << shift_by)) >> shift_by
-            } as u64; 
-
-            result |= ith_bit << i;
+        while stream.pos() < end_pos {
+            args.push(parse_packet(stream, version_sum)?);
+        }
+    } else { // LEN_KIND_SUBPACKETS
+        let num_packets = stream.read(11)?;
+        for _ in 0..num_packets {
+            args.push(parse_packet(stream, version_sum)?);
         }
-
-        result
     }
-}
 
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day16_input.txt");
-    let bytes = common::read_input_bytes(input_path.as_path());
+    apply_op(stream, kind, &args, packet_pos)
+}
 
-    let mut stream = BitStream::new();
-    for i in (0..(bytes.len() - 1)).step_by(2) {
-        let b1 = decode_byte(bytes[i]);
-        let b2 = decode_byte(bytes[i + 1]);
-        let spliced = (b1 << 4) | b2;
-        stream.push(spliced);
-    }
+/// Returns the sum of all packet versions and the value of the outermost packet.
+/// The bytes come from hex, which is what errors point at.
+pub fn evaluate(bytes: &[u8]) -> Result<(u64, u64)> {
+    let mut stream = BitReader::new(bytes, BitOrder::MsbFirst).with_bits_per_column(4);
+    let mut version_sum = 0;
 
-    // for b in &stream.bits {
-    //     print!("{:08b}'", b);
-    // }
-    // println!("");
+    // Whatever follows the outermost packet is padding.
+    let result = parse_packet(&mut stream, &mut version_sum)?;
 
-    fn align_to_n(val: usize, align_to: usize) -> usize {
-        (val + (align_to - 1)) & (!(align_to - 1))
-    }
+    Ok((version_sum, result))
+}
 
-    fn align_to_end_of_packet(stream: &mut BitStream) -> Result<(), ()> {
-        let next_aligned_pos = align_to_n(stream.pos, 4);
-        let result = stream.try_seek(next_aligned_pos); // Try to skip past padding to next package
-        result
-    }
+pub struct Day16;
 
-    fn parse_header(stream: &mut BitStream) -> (u64, u64) {
-        stream.advance(2); // Move to the inital version bits
-        let version = stream.read(3);
+impl Solution for Day16 {
+    type Input = Vec<u8>;
 
-        stream.advance(3); // Move to end of type_id bits
-        let type_id = stream.read(3);
+    fn parse(input: &str) -> Result<Vec<u8>> {
+        decode_hex(input.trim().as_bytes())
+    }
 
-        (version, type_id)
+    fn part1(bytes: &Vec<u8>) -> Result<Answer> {
+        evaluate(bytes)?.0.try_into()
     }
 
-    fn parse_literal_packet(stream: &mut BitStream) -> u64 {
-        let mut digits = Vec::new();
-        digits.reserve(8);
-        loop {
-            stream.advance(1); // Move to "keep_going" bit
-            let keep_going = stream.read(1);
-            
-            stream.advance(4); // Move to next 4 bits of literal
-            digits.push(stream.read(4));
-
-            if keep_going == 0 {
-                break;
-            }
-        }
+    fn part2(bytes: &Vec<u8>) -> Result<Answer> {
+        evaluate(bytes)?.1.try_into()
+    }
+}
 
-        let mut result = 0;
-        let mut count = 0;
-        for digit in digits.iter().rev() {
-            result |= digit << (4 * count);
-            count += 1;
-        } 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::bitstream::BitWriter;
+    use crate::error::AocError;
 
-        result
+    fn part1(hex: &str) -> Answer {
+        Day16::part1(&Day16::parse(hex).unwrap()).unwrap()
     }
 
-    fn parse_operator_num_bits(stream: &mut BitStream) -> u64 {
-        stream.advance(15);
-        stream.read(15)
+    fn part2(hex: &str) -> Answer {
+        Day16::part2(&Day16::parse(hex).unwrap()).unwrap()
     }
 
-    fn parse_operator_num_packets(stream: &mut BitStream) -> u64 {
-        stream.advance(11);
-        stream.read(11)
-    }
-    
-    const PACKET_KIND_LITERAL: u64 = 4;
-    const LEN_KIND_TOTAL_BITS: u64 = 0;
-    const OP_KIND_ADD: u64 = 0;
-    const OP_KIND_MUL: u64 = 1;
-    const OP_KIND_MIN: u64 = 2;
-    const OP_KIND_MAX: u64 = 3;
-    const OP_KIND_GT: u64  = 5;
-    const OP_KIND_LT: u64  = 6;
-    const OP_KIND_EQ: u64  = 7;
-    const _OP_KIND_LITERAL: u64 = 4;
-
-    fn apply_op(kind: u64, args: &[u64]) -> u64 {
-        assert!(!args.is_empty());
-        match kind {
-            OP_KIND_ADD => {
-                args.iter().sum::<u64>()
-            },
-            OP_KIND_MUL => { 
-                args.iter().product::<u64>()
-            },
-            OP_KIND_MIN => {
-                *args.iter().min().unwrap()
-            },
-            OP_KIND_MAX => {
-                *args.iter().max().unwrap()
-            },
-            OP_KIND_GT  => { 
-                assert!(args.len() == 2); 
-                if args[0] > args[1] { 1 } else { 0 } 
-            },
-            OP_KIND_LT  => { 
-                assert!(args.len() == 2); 
-                if args[0] < args[1] { 1 } else { 0 } 
-            },
-            OP_KIND_EQ  => { 
-                assert!(args.len() == 2); 
-                if args[0] == args[1] { 1 } else { 0 } 
-            },
-            _ => panic!(),
-        }
+    #[test]
+    fn version_sums() {
+        assert_eq!(Answer::Int(16), part1("8A004A801A8002F478"));
+        assert_eq!(Answer::Int(12), part1("620080001611562C8802118E34"));
+        assert_eq!(Answer::Int(23), part1("C0015000016115A2E0802F182340"));
+        assert_eq!(Answer::Int(31), part1("A0016C880162017C3686B18A3D4780"));
     }
 
-    #[allow(unused)]
-    fn op_to_str(kind: u64) -> &'static str {
-        match kind {
-            OP_KIND_ADD => "add",
-            OP_KIND_MUL => "mul", 
-            OP_KIND_MIN => "min",
-            OP_KIND_MAX => "max",
-            OP_KIND_GT  => "gt", 
-            OP_KIND_LT  => "lt", 
-            OP_KIND_EQ  => "eq", 
-            _ => panic!(),
-        }
+    #[test]
+    fn expressions() {
+        assert_eq!(Answer::Int(3), part2("C200B40A82"));
+        assert_eq!(Answer::Int(54), part2("04005AC33890"));
+        assert_eq!(Answer::Int(7), part2("880086C3E88112"));
+        assert_eq!(Answer::Int(9), part2("CE00C43D881120"));
+        assert_eq!(Answer::Int(1), part2("D8005AC2A8F0"));
+        assert_eq!(Answer::Int(0), part2("F600BC2D8F"));
+        assert_eq!(Answer::Int(0), part2("9C005AC2F8F0"));
+        assert_eq!(Answer::Int(1), part2("9C0141080250320F1802104A08"));
     }
 
-    fn parse_packet(stream: &mut BitStream, is_subpacket: bool) -> u64 {
-        if is_subpacket { 
-            stream.advance(1)
-        }
-
-        let (_, kind) = parse_header(stream);
-
-        let mut result = 0;
-
-        if kind == PACKET_KIND_LITERAL {
-            result = parse_literal_packet(stream);
-        } 
-        else { // PACKET_KIND_OPERATOR
-            stream.advance(1);
-            let len_kind = stream.read(1);
-  
-            if len_kind == LEN_KIND_TOTAL_BITS {
-                let num_bits = parse_operator_num_bits(stream);
-
-                let end_pos = stream.pos + num_bits as usize;
-
-                let mut args: Vec<u64> = Vec::new();
+    /// A packet tree, written out with the writer and read back in.
+    enum Packet {
+        Literal(u64),
+        Op(u64, Vec<Packet>),
+    }
 
-                while stream.pos < end_pos {
-                    let val = parse_packet(stream, true);
-                    args.push(val);
+    fn write_packet(writer: &mut BitWriter, packet: &Packet) {
+        writer.write(1, 3);
+        match packet {
+            Packet::Literal(value) => {
+                writer.write(PACKET_KIND_LITERAL, 3);
+                let groups = (u64::BITS - value.leading_zeros()).div_ceil(4).max(1);
+                for group in (0..groups).rev() {
+                    writer.write((group > 0) as u64, 1);
+                    writer.write((value >> (4 * group)) & 0xF, 4);
                 }
-
-                result = apply_op(kind, &args);
-                // println!("Len Op {} ({:?}) -> {}", op_to_str(kind), args, result);
-            } else { // LEN_KIND_SUBPACKETS
-                let num_packets = parse_operator_num_packets(stream);
-                let mut args: Vec<u64> = Vec::new();
-
-                for _ in 0..num_packets {
-                    let val = parse_packet(stream, true);
-                    args.push(val);
+            },
+            Packet::Op(kind, args) => {
+                writer.write(*kind, 3);
+                writer.write(1, 1);
+                writer.write(args.len() as u64, 11);
+                for arg in args {
+                    write_packet(writer, arg);
                 }
-
-                result = apply_op(kind, &args);
-                // println!("Pak Op {} ({:?}) -> {}", op_to_str(kind), args, result);
-            }
-        }
-
-        if !is_subpacket {
-            let _ = align_to_end_of_packet(stream);
+            },
         }
-
-        result
     }
 
-    let mut result = 0;
-
-    stream.seek(0);
-    result =  parse_packet(&mut stream, false); 
-
-    println!("Result {}", result);
-
-    // let mut test_stream = BitStream::new();
-
-    // fn write_bit(stream: &mut BitStream, bit: u8) {
-    //     let byte_idx = stream.pos;
-    //     if stream.len <= byte_idx {
-    //         stream.push(0);
-    //     }
-
-    //     let bit_idx = byte_idx % 8;
-    //     let byte_ref = &mut stream.bits[byte_idx / 8];
-    //     // *byte_ref |= bit << bit_idx;
-            
-    //     let shift_by = 7 - bit_idx;
-    //     *byte_ref |= bit << shift_by;
-
-    //     stream.pos += 1;
-    // }
-
-    // fn write_header(stream: &mut BitStream, kind: u64) {
-    //     write_bit(stream, 0);
-    //     write_bit(stream, 0);
-    //     write_bit(stream, 0);
-
-    //     let kind8 = kind as u8;
-    //     write_bit(stream, (kind8 >> 2) & 1);
-    //     write_bit(stream, (kind8 >> 1) & 1);
-    //     write_bit(stream, kind8 & 1);
-    // }
-
-    // fn write_op_len(stream: &mut BitStream, bit_len: u32) {
-    //     write_bit(stream, 0);
-    //     write_bit(stream, ((bit_len >> 14) & 1) as u8);
-    //     write_bit(stream, ((bit_len >> 13) & 1) as u8);
-    //     write_bit(stream, ((bit_len >> 12) & 1) as u8);
-    //     write_bit(stream, ((bit_len >> 11) & 1) as u8);
-    //     write_bit(stream, ((bit_len >> 10) & 1) as u8);
-    //     write_bit(stream, ((bit_len >> 9) & 1) as u8);
-    //     write_bit(stream, ((bit_len >> 8) & 1) as u8);
-    //     write_bit(stream, ((bit_len >> 7) & 1) as u8);
-    //     write_bit(stream, ((bit_len >> 6) & 1) as u8);
-    //     write_bit(stream, ((bit_len >> 5) & 1) as u8);
-    //     write_bit(stream, ((bit_len >> 4) & 1) as u8);
-    //     write_bit(stream, ((bit_len >> 3) & 1) as u8);
-    //     write_bit(stream, ((bit_len >> 2) & 1) as u8);
-    //     write_bit(stream, ((bit_len >> 1) & 1) as u8);
-    //     write_bit(stream,  (bit_len       & 1) as u8);
-    // }
-
-    // fn write_op_packets(stream: &mut BitStream, num_packets: u32) {
-    //     write_bit(stream, 1);
-    //     write_bit(stream, ((num_packets >> 10) & 1) as u8);
-    //     write_bit(stream, ((num_packets >> 9) & 1) as u8);
-    //     write_bit(stream, ((num_packets >> 8) & 1) as u8);
-    //     write_bit(stream, ((num_packets >> 7) & 1) as u8);
-    //     write_bit(stream, ((num_packets >> 6) & 1) as u8);
-    //     write_bit(stream, ((num_packets >> 5) & 1) as u8);
-    //     write_bit(stream, ((num_packets >> 4) & 1) as u8);
-    //     write_bit(stream, ((num_packets >> 3) & 1) as u8);
-    //     write_bit(stream, ((num_packets >> 2) & 1) as u8);
-    //     write_bit(stream, ((num_packets >> 1) & 1) as u8);
-    //     write_bit(stream, (       num_packets & 1) as u8);
-    // }
-
-    // fn write_literal(stream: &mut BitStream, value: u8) {
-    //     write_bit(stream, 0);
-
-    //     write_bit(stream, (value >> 3) & 1);
-    //     write_bit(stream, (value >> 2) & 1);
-    //     write_bit(stream, (value >> 1) & 1);
-    //     write_bit(stream, value & 1);
-    // }
-
-    // write_header(&mut test_stream, OP_KIND_ADD);     // do an add of
-    // write_op_packets(&mut test_stream, 3);
-    // write_header(&mut test_stream, _OP_KIND_LITERAL); // 7
-    // write_literal(&mut test_stream, 7);
-    // write_header(&mut test_stream, _OP_KIND_LITERAL); // 15
-    // write_literal(&mut test_stream, 15);
-
-    // write_header(&mut test_stream, OP_KIND_MAX); // and a max with
-    // write_op_packets(&mut test_stream, 2);
-
-    // write_header(&mut test_stream, OP_KIND_GT); // a greate of
-    // write_op_packets(&mut test_stream, 2);
-    // write_header(&mut test_stream, _OP_KIND_LITERAL); // 5
-    // write_literal(&mut test_stream, 5);
-    // write_header(&mut test_stream, _OP_KIND_LITERAL); // and 3
-    // write_literal(&mut test_stream, 3);
-
-    // write_header(&mut test_stream, OP_KIND_MUL);  // and a mul of 
-    // write_op_packets(&mut test_stream, 3);
-    // write_header(&mut test_stream, _OP_KIND_LITERAL); // 1 
-    // write_literal(&mut test_stream, 1);
-    // write_header(&mut test_stream, _OP_KIND_LITERAL); // 2
-    // write_literal(&mut test_stream, 2);
-    
-    // write_header(&mut test_stream, OP_KIND_ADD); // and a add of 
-    // write_op_packets(&mut test_stream, 3);
-    // write_header(&mut test_stream, _OP_KIND_LITERAL); // 13
-    // write_literal(&mut test_stream, 13);
-    
-    // write_header(&mut test_stream, OP_KIND_MAX); // and a max of 
-    // write_op_packets(&mut test_stream, 2);
-
-    // write_header(&mut test_stream, OP_KIND_ADD); // and an add of
-    // write_op_packets(&mut test_stream, 4);
-    // write_header(&mut test_stream, _OP_KIND_LITERAL); // 7
-    // write_literal(&mut test_stream, 7);
-    // write_header(&mut test_stream, _OP_KIND_LITERAL); // 6
-    // write_literal(&mut test_stream, 6);
-    // write_header(&mut test_stream, _OP_KIND_LITERAL); // 8
-    // write_literal(&mut test_stream, 8);
-    // write_header(&mut test_stream, _OP_KIND_LITERAL); // 9
-    // write_literal(&mut test_stream, 9);
-
-    // write_header(&mut test_stream, OP_KIND_MIN); // and a min of
-    // write_op_packets(&mut test_stream, 2);
-    // write_header(&mut test_stream, _OP_KIND_LITERAL); // 0
-    // write_literal(&mut test_stream, 0);
-    // write_header(&mut test_stream, _OP_KIND_LITERAL); // and 1
-    // write_literal(&mut test_stream, 1);
-
-    // write_header(&mut test_stream, _OP_KIND_LITERAL); // and 1
-    // write_literal(&mut test_stream, 4);
-
-    // for b in &test_stream.bits {
-    //     print!("{:08b}'", b);
-    // }
-    // println!("");
-
-    // test_stream.seek(0);
-    // println!("test result {}", parse_packet(&mut test_stream, false)); 
-}
\ No newline at end of file
+    #[test]
+    fn written_packets() {
+        use Packet::*;
+        let tree = Op(OP_KIND_ADD, vec![Literal(7), Literal(15), Op(OP_KIND_MAX, vec![
+            Op(OP_KIND_GT, vec![Literal(5), Literal(3)]),
+            Op(OP_KIND_MUL, vec![Literal(1), Literal(2), Op(OP_KIND_ADD, vec![
+                Literal(13),
+                Op(OP_KIND_MAX, vec![
+                    Op(OP_KIND_ADD, vec![Literal(7), Literal(6), Literal(8), Literal(9)]),
+                    Op(OP_KIND_MIN, vec![Literal(0), Literal(1)]),
+                ]),
+                Literal(4),
+            ])]),
+        ])]);
+
+        let mut writer = BitWriter::new(BitOrder::MsbFirst);
+        write_packet(&mut writer, &tree);
+        writer.align(4);
+        assert_eq!(Answer::Int(22), part1(&writer.to_hex()));
+        assert_eq!(Answer::Int(7 + 15 + 2 * (13 + 30 + 4)), part2(&writer.to_hex()));
+    }
+
+    #[test]
+    fn overflow() {
+        use Packet::*;
+        let evaluate_tree = |tree: Packet| {
+            let mut writer = BitWriter::new(BitOrder::MsbFirst);
+            write_packet(&mut writer, &tree);
+            writer.align(4);
+            Day16::part2(&Day16::parse(&writer.to_hex()).unwrap())
+        };
+
+        assert_eq!(Answer::Int(1 << 40), evaluate_tree(Literal(1 << 40)).unwrap());
+        assert!(evaluate_tree(Op(OP_KIND_ADD, vec![Literal(u64::MAX), Literal(1)])).is_err());
+        assert!(evaluate_tree(Op(OP_KIND_MUL, vec![Literal(1 << 40), Literal(1 << 30)])).is_err());
+        assert!(matches!(evaluate_tree(Literal(u64::MAX)), Err(AocError::NoSolution { .. })));
+    }
+
+    #[test]
+    fn truncated() {
+        // The literal in "D2FE28" cut short in its last group.
+        let err = Day16::parse("D2FE").and_then(|bytes| evaluate(&bytes)).unwrap_err();
+        assert!(matches!(err, AocError::Parse { line: 1, column: 5, .. }), "{}", err);
+    }
+}
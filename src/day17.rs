@@ -1,42 +1,44 @@
+use super::common;
+use super::common::vec;
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 use std::collections::HashSet;
-use std::path::Path;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-struct Vec2 {
-    x: i32,
-    y: i32,
-}
+pub type Vec2 = vec::Vec2<i32>;
 
-impl Vec2 {
-    pub fn new(x: i32, y: i32) -> Vec2 {
-        Vec2 { x, y }
-    }
+/// Parses "target area: x=20..30, y=-10..-5" into the (min, max) corners of the target.
+fn parse_target(bytes: &[u8]) -> Result<(Vec2, Vec2)> {
+    let mut values = Vec::with_capacity(4);
 
-    pub fn zero() -> Vec2 {
-        Vec2 { x: 0, y: 0 }
+    let mut cursor = 0;
+    while cursor < bytes.len() {
+        if bytes[cursor].is_ascii_digit() || bytes[cursor] == b'-' {
+            let (num, new_cursor) = common::parse_int::<i32>(bytes, cursor)?;
+            values.push(num);
+            cursor = new_cursor;
+        } else {
+            cursor += 1;
+        }
     }
 
-    pub fn add(self, other: Vec2) -> Vec2 {
-        Vec2 { x: self.x + other.x, y: self.y + other.y }
+    if values.len() != 4 {
+        return Err(AocError::parse(1, 1, format!("Expected 4 numbers, found {}", values.len())));
     }
-}
 
-pub fn run(_: &Path) {
-    let (target_min, target_max) = {
-        let target_x = Vec2::new(102, 157);
-        let target_y = Vec2::new(-146, -90);
-        // let target_x = Vec2::new(20, 30);
-        // let target_y = Vec2::new(-10, -5);
-    
-        assert!(target_x.x > 0 && target_x.y > 0);
+    let target_x = Vec2::new(values[0], values[1]);
+    let target_y = Vec2::new(values[2], values[3]);
 
-        (Vec2::new(i32::min(target_x.x, target_x.y), i32::min(target_y.x, target_y.y)),
-         Vec2::new(i32::max(target_x.x, target_x.y), i32::max(target_y.x, target_y.y)))
-    };
+    if target_x.x <= 0 || target_x.y <= 0 || target_y.x >= 0 || target_y.y >= 0 {
+        return Err(AocError::parse(1, 1, "Expected the target to be right of and below the start"));
+    }
 
-    println!("Target min ({} {}) max ({} {})", target_min.x, target_min.y,
-        target_max.x, target_max.y);
+    Ok((Vec2::new(i32::min(target_x.x, target_x.y), i32::min(target_y.x, target_y.y)),
+        Vec2::new(i32::max(target_x.x, target_x.y), i32::max(target_y.x, target_y.y))))
+}
 
+/// Tries all initial velocities and returns the highest peak reached
+/// by any shot that hits the target, and the set of velocities that hit.
+fn fire_all(target_min: Vec2, target_max: Vec2) -> (i32, HashSet<Vec2>) {
     // let start_x = { // optimal x
     //     let mut x = 0;
     //     for i in 1..target_min.x {
@@ -53,7 +55,6 @@ pub fn run(_: &Path) {
     let end_x = target_max.x ;
 
     let mut max_y = i32::MIN;
-    let mut max_vel = None;
 
     let l_y = target_min.y;
     let u_y = target_min.y.abs();
@@ -70,24 +71,21 @@ pub fn run(_: &Path) {
         let mut peak = i32::MIN;
 
         'inner: loop {
-            pos = pos.add(vel);
+            pos += vel;
             peak = i32::max(peak, pos.y);
 
-            if (pos.x >= target_min.x) && (pos.x <= target_max.x) {
-                if (pos.y >= target_min.y) && (pos.y <= target_max.y) {
-                    hit_vel = Some(initial_vel);
-                    break 'inner;
-                }
+            if (pos.x >= target_min.x) && (pos.x <= target_max.x) &&
+               (pos.y >= target_min.y) && (pos.y <= target_max.y) {
+                hit_vel = Some(initial_vel);
+                break 'inner;
             }
     
             if target_max.x >= 0 {
                 if pos.x > target_max.x {
                     break 'inner;
                 }
-            } else {
-                if pos.x < target_max.x {
-                    break 'inner;
-                }
+            } else if pos.x < target_max.x {
+                break 'inner;
             }
     
             if vel.x == 0 {
@@ -95,10 +93,8 @@ pub fn run(_: &Path) {
                     if pos.x < target_min.x {
                         break 'inner;
                     }
-                } else {
-                    if pos.x > target_min.x {
-                        break 'inner;
-                    }
+                } else if pos.x > target_min.x {
+                    break 'inner;
                 }
     
                 if pos.y < target_max.y {
@@ -114,18 +110,43 @@ pub fn run(_: &Path) {
             }
         }
 
-        match hit_vel {
-            Some(v) => {
-                hits.insert(v);
-                if peak > max_y {
-                    max_y = peak;
-                    max_vel = hit_vel;
-                }
-            },
-            None => ()
+        if let Some(v) = hit_vel {
+            hits.insert(v);
+            max_y = i32::max(max_y, peak);
         }
     } 
     }
-    println!("Max y {} at vel {} {}", max_y, max_vel.unwrap().x, max_vel.unwrap().y);
-    println!("Num distinct velocities {}", hits.len());
-}
\ No newline at end of file
+
+    (max_y, hits)
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Input = (Vec2, Vec2);
+
+    fn parse(input: &str) -> Result<(Vec2, Vec2)> {
+        parse_target(input.as_bytes())
+    }
+
+    fn part1(&(target_min, target_max): &(Vec2, Vec2)) -> Result<Answer> {
+        Ok(fire_all(target_min, target_max).0.into())
+    }
+
+    fn part2(&(target_min, target_max): &(Vec2, Vec2)) -> Result<Answer> {
+        fire_all(target_min, target_max).1.len().try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
+
+    const EXAMPLE: &str = "target area: x=20..30, y=-10..-5\n";
+
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(45), Answer::Int(112)), solve_example::<Day17>(EXAMPLE));
+    }
+}
@@ -1,10 +1,10 @@
-use super::common;
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 use std::fmt;
-use std::path::Path;
 use std::collections::VecDeque;
 
 #[derive(Clone, Copy, PartialEq)]
-enum Digit {
+pub enum Digit {
     ScopeOpen,
     ScopeClose,
     Literal(u32)
@@ -13,8 +13,8 @@ enum Digit {
 impl fmt::Display for Digit {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Digit::ScopeOpen => write!(f, "{}", '['),
-            Digit::ScopeClose => write!(f, "{}", ']'),
+            Digit::ScopeOpen => write!(f, "["),
+            Digit::ScopeClose => write!(f, "]"),
             Digit::Literal(x) => write!(f, "{}", x),
         }
     }
@@ -26,96 +26,100 @@ impl fmt::Debug for Digit {
     }
 }
 
-#[derive(Clone, Copy, PartialEq)]
-enum ParseResult {
-    Value(Digit),
-    Comma,
-    Newline,
-    Ignore,
-}
-
-fn char_to_digit(c: char) -> ParseResult {
-    match c {
-        '[' => ParseResult::Value(Digit::ScopeOpen),
-        ']' => ParseResult::Value(Digit::ScopeClose),
-        '0'..='9' => {
-            let val = (c as u8) - ('0' as u8);
-            ParseResult::Value(Digit::Literal(val as u32))
-        },
-        ',' => ParseResult::Comma,
-        '\n' | '\r' => ParseResult::Newline,
-        ' ' => ParseResult::Ignore,    
-        t => panic!("Unexpected token \'{}\'", t),
-    }
-}
-
-fn build_literal(digits: &mut Vec<u32>) -> u32 {
-    digits.reverse();
-
-    let mut literal = 0;
-    for i in 0..digits.len() {
-        literal += digits[i] * 10u32.pow(i as u32);
-    }
-    digits.clear();
+/// How deep pairs from the input may nest. A reduced number only goes four deep, but the
+/// reducer copes with one more level as long as that's a pair of regular numbers.
+const MAX_INPUT_DEPTH: usize = 5;
 
-    literal
-}
-
-fn numbers_from_byte_slice(bytes: &[u8]) -> Vec<VecDeque<Digit>> {
+fn numbers_from_byte_slice(bytes: &[u8]) -> Result<Vec<VecDeque<Digit>>> {
     let mut all_numbers = Vec::new();
     let mut number = VecDeque::new();
-    let mut literal_buf = Vec::new();
-
-    for b in bytes {
-        match char_to_digit(*b as char) {
-            ParseResult::Value(value) => {
-                match value {
-                    Digit::ScopeClose => {
-                        if !literal_buf.is_empty() {
-                            let literal = build_literal(&mut literal_buf);
-                            number.push_back(Digit::Literal(literal));
-                        }
-                        
-                        number.push_back(value)
-                    },
-                    Digit::ScopeOpen => number.push_back(value),
-                    Digit::Literal(x) => literal_buf.push(x), 
+    // The number of halves and commas seen so far in each pair that's still open.
+    let mut open_pairs: Vec<(usize, usize)> = Vec::new();
+
+    // Counts a new half towards the innermost pair, which has to have room for it.
+    let add_half = |open_pairs: &mut Vec<(usize, usize)>, number: &VecDeque<Digit>, offset: usize| {
+        match open_pairs.last_mut() {
+            Some((halves, commas)) if *halves == *commas && *halves < 2 => {
+                *halves += 1;
+                Ok(())
+            },
+            Some(_) => Err(AocError::parse_at(bytes, offset, "Expected ',' or ']'")),
+            None if number.is_empty() => Err(AocError::parse_at(bytes, offset, "Expected '['")),
+            None => Err(AocError::parse_at(bytes, offset, "Expected a new line after the number")),
+        }
+    };
+
+    let mut offset = 0;
+    while let Some(b) = bytes.get(offset) {
+        match *b {
+            b'[' => {
+                if !open_pairs.is_empty() || !number.is_empty() {
+                    add_half(&mut open_pairs, &number, offset)?;
                 }
+                if open_pairs.len() == MAX_INPUT_DEPTH {
+                    return Err(AocError::parse_at(bytes, offset,
+                        format!("Pairs can't be nested more than {} deep", MAX_INPUT_DEPTH)));
+                }
+                open_pairs.push((0, 0));
+                number.push_back(Digit::ScopeOpen);
             },
-            ParseResult::Comma => {
-                if !literal_buf.is_empty() {
-                    let literal = build_literal(&mut literal_buf);
-                    number.push_back(Digit::Literal(literal));
+            b'0'..=b'9' => {
+                add_half(&mut open_pairs, &number, offset)?;
+                // Inputs are reduced, and reducing anything bigger takes time in proportion to it.
+                if bytes.get(offset + 1).is_some_and(u8::is_ascii_digit) {
+                    return Err(AocError::parse_at(bytes, offset, "Regular numbers in the input are single digits"));
                 }
+                number.push_back(Digit::Literal((*b - b'0') as u32));
+            },
+            b',' => match open_pairs.last_mut() {
+                Some((1, commas)) if *commas == 0 => *commas = 1,
+                _ => return Err(AocError::parse_at(bytes, offset, "Unexpected ','")),
             },
-            ParseResult::Newline => {
-                assert!(literal_buf.is_empty());
+            b']' => match open_pairs.pop() {
+                Some((2, 1)) => number.push_back(Digit::ScopeClose),
+                Some(_) => return Err(AocError::parse_at(bytes, offset, "Expected a pair of two elements")),
+                None => return Err(AocError::parse_at(bytes, offset, "Unmatched ']'")),
+            },
+            b'\n' | b'\r' => {
+                if !open_pairs.is_empty() {
+                    return Err(AocError::parse_at(bytes, offset, "Expected the number to end with ']'"));
+                }
                 if !number.is_empty() {
-                    all_numbers.push(number);
-                    number = VecDeque::new();
+                    all_numbers.push(std::mem::take(&mut number));
                 }
             },
-            ParseResult::Ignore => (),
+            b' ' => (),
+            _ => return Err(AocError::parse_at(bytes, offset, format!("Unexpected token '{}'", *b as char))),
         }
+        offset += 1;
     }
 
-    all_numbers.push(number);
-    all_numbers
+    if !open_pairs.is_empty() {
+        return Err(AocError::parse_at(bytes, bytes.len(), "Expected the number to end with ']'"));
+    }
+
+    if !number.is_empty() {
+        all_numbers.push(number);
+    }
+
+    if all_numbers.is_empty() {
+        return Err(AocError::parse(1, 1, "Expected at least one number"));
+    }
+
+    Ok(all_numbers)
 }
 
 #[must_use]
 fn explode_at(number: &mut VecDeque<Digit>, scope_open_idx: usize) -> VecDeque<Digit> {
-    let mut exploded = VecDeque::new();
-    exploded.reserve(number.len());
+    let mut exploded = VecDeque::with_capacity(number.len());
 
     let mut explode_to_left_idx = None;
 
     for _ in 0..scope_open_idx { // Only go up to the scope, we dont want to push the exploded scope.
         match number.pop_front() {
             Some(digit) => {
-                match digit {
-                    Digit::Literal(_) => explode_to_left_idx = Some(exploded.len()),
-                    _ => ()
+                if let Digit::Literal(_) = digit {
+                    explode_to_left_idx = Some(exploded.len());
                 }
                 exploded.push_back(digit);
             },
@@ -146,11 +150,10 @@ fn explode_at(number: &mut VecDeque<Digit>, scope_open_idx: usize) -> VecDeque<D
     while !number.is_empty() {
         match number.pop_front() {
             Some(digit) => {
-                match digit {
-                    Digit::Literal(_) => if explode_to_right_idx.is_none() {
+                if let Digit::Literal(_) = digit {
+                    if explode_to_right_idx.is_none() {
                         explode_to_right_idx = Some(exploded.len());
-                    },
-                    _ => ()
+                    }
                 }
                 exploded.push_back(digit);
             },
@@ -159,16 +162,13 @@ fn explode_at(number: &mut VecDeque<Digit>, scope_open_idx: usize) -> VecDeque<D
     }
 
     fn explode_into(number: &mut VecDeque<Digit>, idx: Option<usize>, value: u32) {
-        match idx {
-            Some(i) => {
-                match &mut number[i] {
-                    Digit::Literal(x) => {
-                        *x += value;
-                    },
-                    _ => panic!()
-                }
-            },
-            _ => (),
+        if let Some(i) = idx {
+            match &mut number[i] {
+                Digit::Literal(x) => {
+                    *x += value;
+                },
+                _ => panic!()
+            }
         }
     }
 
@@ -180,8 +180,7 @@ fn explode_at(number: &mut VecDeque<Digit>, scope_open_idx: usize) -> VecDeque<D
 
 #[must_use]
 fn split_at(number: &mut VecDeque<Digit>, split_digit_idx: usize) -> VecDeque<Digit> {
-    let mut split = VecDeque::new();
-    split.reserve(number.len());
+    let mut split = VecDeque::with_capacity(number.len());
 
     for _ in 0..split_digit_idx { // Only go up to before the digit
         match number.pop_front() {
@@ -193,17 +192,12 @@ fn split_at(number: &mut VecDeque<Digit>, split_digit_idx: usize) -> VecDeque<Di
     }
 
     let split_literal = match number.pop_front() {
-        Some(digit) => {
-            match digit {
-                Digit::Literal(x) => x,
-                _ => panic!(),
-            }
-        },
-        None => panic!()
+        Some(Digit::Literal(x)) => x,
+        _ => panic!()
     };
 
     let left_val = split_literal / 2;
-    let right_val = ((split_literal as f32) / 2.0).round() as u32;
+    let right_val = split_literal - left_val;
 
     split.push_back(Digit::ScopeOpen);
     split.push_back(Digit::Literal(left_val));
@@ -221,8 +215,8 @@ fn split_at(number: &mut VecDeque<Digit>, split_digit_idx: usize) -> VecDeque<Di
 fn find_next_explode(number: &VecDeque<Digit>) -> Option<usize> {
     let mut scopes = 0;
     
-    for i in 0..number.len() {
-        match number[i] {
+    for (i, digit) in number.iter().enumerate() {
+        match digit {
             Digit::ScopeOpen => {
                 scopes += 1;
                 if scopes > 4 {
@@ -241,16 +235,7 @@ fn find_next_explode(number: &VecDeque<Digit>) -> Option<usize> {
 }
 
 fn find_next_split(number: &VecDeque<Digit>) -> Option<usize> {        
-    for i in 0..number.len() {
-        match number[i] {
-            Digit::Literal(x) => if x > 9 {
-                return Some(i);
-            },
-            _ => (),
-        };
-    }
-
-    None
+    number.iter().position(|digit| matches!(digit, Digit::Literal(x) if *x > 9))
 }
 
 fn reduce(mut number: VecDeque<Digit>) -> VecDeque<Digit> {
@@ -273,21 +258,6 @@ fn reduce(mut number: VecDeque<Digit>) -> VecDeque<Digit> {
     number
 }
 
-fn print_number(number: &VecDeque<Digit>) {
-    for i in 0..number.len() {
-        print!("{}", number[i]);
-        
-        if i < (number.len() - 1) {
-            match (number[i], number[i+1]) {
-                (Digit::Literal(_), Digit::Literal(_)) => print!(","),
-                (Digit::ScopeClose, Digit::ScopeOpen) => print!(","),
-                _ => (),    
-            }
-        } 
-    }
-    println!();
-}
-
 fn add_numbers(into: &mut VecDeque<Digit>, from: &VecDeque<Digit>) {
     into.push_front(Digit::ScopeOpen);
 
@@ -324,48 +294,56 @@ fn mag(number: &VecDeque<Digit>) -> u64 {
     stack[0]
 }
 
+/// Puzzle inputs are reduced already. Making sure of it keeps sums from nesting deeper than
+/// explode_at() can handle.
+fn reduce_all(numbers: &[VecDeque<Digit>]) -> Vec<VecDeque<Digit>> {
+    numbers.iter().map(|number| reduce(number.clone())).collect()
+}
 
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day18_input.txt");
-    let bytes = common::read_input_bytes(input_path.as_path());
-    
-    let numbers = numbers_from_byte_slice(&bytes);
+pub struct Day18;
 
-    let mut result: VecDeque<Digit> = numbers[0].clone();
-    result = reduce(result);
+impl Solution for Day18 {
+    type Input = Vec<VecDeque<Digit>>;
 
-    for number in &numbers[1..] {
-        add_numbers(&mut result, number);
-        result = reduce(result);
-    } 
+    fn parse(input: &str) -> Result<Vec<VecDeque<Digit>>> {
+        numbers_from_byte_slice(input.as_bytes())
+    }
 
-    print_number(&result);
-    println!("Magnitude: {}", mag(&result));
+    fn part1(numbers: &Vec<VecDeque<Digit>>) -> Result<Answer> {
+        let numbers = reduce_all(numbers);
+        let mut result: VecDeque<Digit> = numbers[0].clone();
 
-    let mut max_mag = 0;
-    for outer in 0..numbers.len() {
-        for inner in (outer + 1)..numbers.len() {
-            if inner == outer {
-                continue;
-            }
+        for number in &numbers[1..] {
+            add_numbers(&mut result, number);
+            result = reduce(result);
+        }
 
-            {
-                let mut result = numbers[outer].clone(); 
-                add_numbers(&mut result, &numbers[inner]);
-                result = reduce(result);
-                max_mag = u64::max(max_mag, mag(&result));
-            }
+        mag(&result).try_into()
+    }
 
-            {
-                let mut result = numbers[inner].clone(); 
-                add_numbers(&mut result, &numbers[outer]);
-                result = reduce(result);
-                max_mag = u64::max(max_mag, mag(&result));
+    fn part2(numbers: &Vec<VecDeque<Digit>>) -> Result<Answer> {
+        let numbers = reduce_all(numbers);
+        let mut max_mag = 0;
+        for outer in 0..numbers.len() {
+            for inner in (outer + 1)..numbers.len() {
+                {
+                    let mut result = numbers[outer].clone();
+                    add_numbers(&mut result, &numbers[inner]);
+                    result = reduce(result);
+                    max_mag = u64::max(max_mag, mag(&result));
+                }
+
+                {
+                    let mut result = numbers[inner].clone();
+                    add_numbers(&mut result, &numbers[outer]);
+                    result = reduce(result);
+                    max_mag = u64::max(max_mag, mag(&result));
+                }
             }
         }
-    }
 
-    println!("Max possible mag of two adds {}", max_mag);
+        max_mag.try_into()
+    }
 }
 
 #[cfg(test)]
@@ -374,7 +352,7 @@ mod tests {
 
     fn number_from_str_slice(str: &str) -> Vec<VecDeque<super::Digit>> {
         let bytes = str.chars().map(|c| c as u8).collect::<Vec<_>>();
-        super::numbers_from_byte_slice(&bytes)
+        super::numbers_from_byte_slice(&bytes).unwrap()
     }
     
     fn check(from_str: &str, to_str: &str) {
@@ -414,7 +392,11 @@ mod tests {
 
     #[test]
     fn split1() {
-        check("[[3,10],[1,[11,2]]]", "[[3,[5,5]],[1,[[5,6],2]]]");
+        use super::Digit::*;
+        // The parser only takes single digits, so put "[[3,10],[1,[11,2]]]" together by hand.
+        let from = VecDeque::from([ScopeOpen, ScopeOpen, Literal(3), Literal(10), ScopeClose,
+            ScopeOpen, Literal(1), ScopeOpen, Literal(11), Literal(2), ScopeClose, ScopeClose, ScopeClose]);
+        assert_eq!(number_from_str_slice("[[3,[5,5]],[1,[[5,6],2]]]")[0], super::reduce(from));
     }
 
     #[test]
@@ -483,4 +465,34 @@ mod tests {
         let number = &number_from_str_slice("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]")[0];
         assert_eq!(3488, super::mag(number));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn malformed() {
+        for (input, column) in [("[[1,2]]", 7), ("[1,2,3]", 5), ("[1]", 3), ("[,1]", 2), ("[1,2][3,4]", 6),
+                                ("[[[[[[1,2],3],4],5],6],7]", 6), ("12", 1), ("[1 2]", 4), ("[1,2]]", 6), ("[1,10]", 4)] {
+            match super::numbers_from_byte_slice(input.as_bytes()) {
+                Err(crate::error::AocError::Parse { column: col, .. }) => assert_eq!(column, col, "{}", input),
+                _ => panic!("Expected {} to be rejected", input),
+            }
+        }
+    }
+
+    const EXAMPLE: &str = "\
+[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
+[[[5,[2,8]],4],[5,[[9,9],0]]]
+[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
+[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
+[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
+[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
+[[[[5,4],[7,7]],8],[[8,3],8]]
+[[9,3],[[9,9],[6,[4,9]]]]
+[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
+[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]
+";
+
+    #[test]
+    fn example() {
+        use crate::solution::{solve_example, Answer};
+        assert_eq!((Answer::Int(4140), Answer::Int(3993)), solve_example::<super::Day18>(EXAMPLE));
+    }
+}
@@ -1,56 +1,18 @@
 use super::common;
-use std::fmt;
-use std::hash::Hash;
-use std::{path::Path, io::BufRead};
+use super::common::float::{ApproxEq, Tolerance};
+use super::common::rotation::{Rotation, Transform};
+use super::common::vec;
+use crate::scan;
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 use std::collections::{HashSet, HashMap};
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
-struct Vec3 {
-    x: i32,
-    y: i32,
-    z: i32
-}
-
-impl Vec3 {
-    fn new(x: i32, y: i32, z: i32) -> Vec3 {
-        Vec3 {x, y, z}
-    }
-
-    fn zero() -> Vec3 {
-        Vec3 {x: 0, y: 0, z: 0}
-    }
-
-    fn add(lhs: Vec3, rhs: Vec3) -> Vec3 {
-        Vec3::new(
-            lhs.x + rhs.x,
-            lhs.y + rhs.y,
-            lhs.z + rhs.z
-        )
-    }
+type Vec3 = vec::Vec3<i32>;
 
-    fn sub(lhs: Vec3, rhs: Vec3) -> Vec3 {
-        Vec3::new(
-            lhs.x - rhs.x,
-            lhs.y - rhs.y,
-            lhs.z - rhs.z
-        )
-    }
-
-    fn abs(&self) -> Vec3 {
-        Vec3 { x: self.x.abs(), y: self.y.abs(), z: self.z.abs() }
-    }
-
-    fn fmag(&self) -> f32 {
-        (self.x as f32 * self.x as f32 
-       + self.y as f32 * self.y as f32 
-       + self.z as f32 * self.z as f32).sqrt()
-    }
-}
-
-impl fmt::Display for Vec3 {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} {} {}", self.x, self.y, self.z)
-    }
+fn fmag(v: Vec3) -> f32 {
+    (v.x as f32 * v.x as f32
+   + v.y as f32 * v.y as f32
+   + v.z as f32 * v.z as f32).sqrt()
 }
 
 #[derive(Clone, Copy, Default)]
@@ -65,11 +27,13 @@ struct Signal {
     pos: Vec3
 }
 
+/// The signals stay in the scanner's own coordinates. Once aligned, `to_first` maps them
+/// into the coordinates of the first scanner.
 #[derive(Clone, Default)]
 struct Scanner {
     signals: Vec<Signal>,
-    similar: Vec<HashMap<usize, Similarity>>, // todo make tis a big vec
-    pos: Vec3
+    similar: Vec<HashMap<usize, Similarity>>,
+    to_first: Transform<i32>,
 }
 
 fn compare_signals(this_similar: &HashMap<usize, Similarity>, other_similar: &HashMap<usize, Similarity>,
@@ -78,14 +42,13 @@ fn compare_signals(this_similar: &HashMap<usize, Similarity>, other_similar: &Ha
 
     for (this_idx, this_cmp) in this_similar {
         let found = other_similar.iter().find(|(_, other_cmp)| {
-            common::f32_near_equal(this_cmp.delta_mag, other_cmp.delta_mag) &&
             (this_cmp.d_min == other_cmp.d_min) &&
-            (this_cmp.d_max == other_cmp.d_max)
+            (this_cmp.d_max == other_cmp.d_max) &&
+            this_cmp.delta_mag.approx_eq(other_cmp.delta_mag, Tolerance::DEFAULT)
         });
 
-        match found {
-            Some(other_kvp) => out_hits.push((*other_kvp.1, *this_idx, *other_kvp.0)),
-            None => (),
+        if let Some(other_kvp) = found {
+            out_hits.push((*other_kvp.1, *this_idx, *other_kvp.0));
         }
     }
 }
@@ -99,9 +62,9 @@ struct Intersection {
 
 impl Scanner {
     fn update_similarity(&mut self, idx: usize, new_idx: usize) {
-        let delta_abs = Vec3::sub(self.signals[idx].pos, self.signals[new_idx].pos).abs();
+        let delta_abs = (self.signals[idx].pos - self.signals[new_idx].pos).map(i32::abs);
         
-        let delta_mag = delta_abs.fmag();
+        let delta_mag = fmag(delta_abs);
         let d_min = i32::min(delta_abs.x, i32::min(delta_abs.y, delta_abs.z));
         let d_max = i32::max(delta_abs.x, i32::max(delta_abs.y, delta_abs.z));
         let similarity = Similarity{ delta_mag, d_min, d_max };
@@ -139,83 +102,73 @@ impl Scanner {
         None
     }
 
-    fn transform_points_from_intersect(probes: &mut[Scanner], this_idx: usize, other_idx: usize, intersect: &Intersection) {
+    /// Works out where the other scanner is and which way it faces from the beacons both see,
+    /// and chains that onto the way from this scanner to the first one. Returns false if none
+    /// of the shared beacons pin down a single rotation.
+    fn align_from_intersect(probes: &mut [Scanner], this_idx: usize, other_idx: usize, intersect: &Intersection) -> bool {
         for hit in &intersect.similarities {
             if hit.0.d_min == 0 { continue; }
 
-            let dt_0 = Vec3::sub(probes[this_idx].signals[intersect.from].pos, probes[this_idx].signals[hit.2].pos);
-            let dt_1 = Vec3::sub(probes[other_idx].signals[intersect.to].pos, probes[other_idx].signals[hit.1].pos);
+            let dt_0 = probes[this_idx].signals[intersect.from].pos - probes[this_idx].signals[hit.2].pos;
+            let dt_1 = probes[other_idx].signals[intersect.to].pos - probes[other_idx].signals[hit.1].pos;
 
+            // With two axes of the same length, more than one rotation lines the deltas up.
             if dt_0.x.abs() == dt_0.y.abs() || dt_0.z.abs() == dt_0.y.abs() || dt_0.x.abs() == dt_0.z.abs() {
                 continue;
             }
-            
-            let axis_map = [
-                if dt_0.x == dt_1.x { 1 } else if dt_0.x == -dt_1.x { -1 } else { 0 }, 
-                if dt_0.y == dt_1.x { 1 } else if dt_0.y == -dt_1.x { -1 } else { 0 },
-                if dt_0.z == dt_1.x { 1 } else if dt_0.z == -dt_1.x { -1 } else { 0 },
-                if dt_0.x == dt_1.y { 1 } else if dt_0.x == -dt_1.y { -1 } else { 0 },
-                if dt_0.y == dt_1.y { 1 } else if dt_0.y == -dt_1.y { -1 } else { 0 },
-                if dt_0.z == dt_1.y { 1 } else if dt_0.z == -dt_1.y { -1 } else { 0 },
-                if dt_0.x == dt_1.z { 1 } else if dt_0.x == -dt_1.z { -1 } else { 0 },
-                if dt_0.y == dt_1.z { 1 } else if dt_0.y == -dt_1.z { -1 } else { 0 },
-                if dt_0.z == dt_1.z { 1 } else if dt_0.z == -dt_1.z { -1 } else { 0 }
-            ];
-
-            for signal in &mut probes[other_idx].signals {
-                let pre = signal.pos;
-                signal.pos.x = pre.x * axis_map[0] + pre.y * axis_map[3] + pre.z * axis_map[6];
-                signal.pos.y = pre.x * axis_map[1] + pre.y * axis_map[4] + pre.z * axis_map[7];
-                signal.pos.z = pre.x * axis_map[2] + pre.y * axis_map[5] + pre.z * axis_map[8];
-            }
+
+            let Some(rotation) = Rotation::all().into_iter().find(|r| r.apply(dt_1) == dt_0) else {
+                continue;
+            };
 
             let from = probes[this_idx].signals[intersect.from].pos;
             let to = probes[other_idx].signals[intersect.to].pos;
-            probes[other_idx].pos = Vec3::sub(from, to);
+            let other_to_this = Transform::new(rotation, from - rotation.apply(to));
 
-            let other = &mut probes[other_idx];
-
-            for signal in &mut other.signals {
-                signal.pos = Vec3::add(signal.pos, other.pos);
-            }
-
-            // println!("Scanner {} at {}", other_idx, other.pos);
-            break;
+            probes[other_idx].to_first = other_to_this.then(&probes[this_idx].to_first);
+            return true;
         }
+
+        false
     }
 }
 
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day19_input.txt");
-    let file = std::fs::File::open(input_path).unwrap();
-    let reader = std::io::BufReader::new(file);
+fn parse_scanners(input: &str) -> Result<Vec<Scanner>> {
     let mut probes: Vec<Scanner> = Vec::new();
-
-    for res in reader.lines() {
-        let line = res.unwrap();
-        let c1 = line.chars().take(1).next();
-        if c1.is_none() {
-            continue;
-        }
-        let c2 = line.chars().skip(1).take(1).next().unwrap();
-        if c2 == '-' {
-            probes.push(Scanner::default());
-        } else {
-            let bytes = line.bytes().collect::<Vec<_>>();
-            let values = common::read_list_of_csv_i32s(&bytes);
-            assert!(values.len() == 3);
-            
-            let scanner = probes.last_mut().unwrap();
-            scanner.add(Vec3::new(values[0], values[1], values[2]));
+    let mut lexer = common::Scanner::new(input.as_bytes());
+
+    lexer.skip_ws();
+    while !lexer.is_at_end() {
+        let [_id]: [u32; 1] = scan!(lexer, "--- scanner {} ---")?;
+        let mut scanner = Scanner::default();
+
+        lexer.skip_ws();
+        while !lexer.is_at_end() && !lexer.looking_at("--") {
+            let [x, y, z] = scan!(lexer, "{},{},{}")?;
+            scanner.add(Vec3::new(x, y, z));
+            lexer.skip_ws();
         }
+
+        probes.push(scanner);
+    }
+
+    if probes.is_empty() {
+        return Err(AocError::parse(1, 1, "Expected at least one scanner"));
     }
 
+    Ok(probes)
+}
+
+/// Transforms every scanner into the space of the first one.
+fn align_scanners(probes: &mut [Scanner]) -> Result<()> {
     let mut visited = vec![false; probes.len()];
 
-    probes[0].pos = Vec3::zero(); // Assume the first probe as our "absolute" space
+    probes[0].to_first = Transform::identity(); // Assume the first probe as our "absolute" space
     visited[0] = true;
 
     while visited.iter().any(|v| !v) {
+        let num_visited = visited.iter().filter(|v| **v).count();
+
         for i in 0..probes.len() {
             for j in 0..probes.len() {
                 if i == j || !visited[i] || visited[j] {
@@ -227,38 +180,231 @@ pub fn run(root_dir: &Path) {
                     None => continue,
                     Some(mut intrs) => {
                         intrs.similarities.sort_by(|a, b| {
-                            return a.1.cmp(&b.1).then_with(|| {
+                            a.1.cmp(&b.1).then_with(|| {
                                 a.2.cmp(&b.2)
                             })
                         });
 
-                        Scanner::transform_points_from_intersect(&mut probes, i, j, &intrs);
-                        visited[j] = true;
+                        // Another scanner might still manage to align it.
+                        visited[j] = Scanner::align_from_intersect(probes, i, j, &intrs);
                     }
                 }
 
             }
         }
+
+        if visited.iter().filter(|v| **v).count() == num_visited {
+            return Err(AocError::no_solution("Some scanners can't be aligned with any other"));
+        }
     }
 
-    let mut all_signals = HashSet::new();
-    for scanner in &probes {
-        for signal in &scanner.signals {
-            all_signals.insert(signal.pos);
+    Ok(())
+}
+
+/// The scanners after alignment, all in the space of the first scanner.
+pub struct AlignedScanners {
+    beacons: HashSet<Vec3>,
+    scanner_positions: Vec<Vec3>,
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    type Input = AlignedScanners;
+
+    // Aligning the scanners is the expensive bit and both parts need it, so it's done here.
+    fn parse(input: &str) -> Result<AlignedScanners> {
+        let mut probes = parse_scanners(input)?;
+        align_scanners(&mut probes)?;
+
+        let mut beacons = HashSet::new();
+        for scanner in &probes {
+            for signal in &scanner.signals {
+                beacons.insert(scanner.to_first.apply(signal.pos));
+            }
         }
+
+        Ok(AlignedScanners {
+            beacons,
+            scanner_positions: probes.iter().map(|p| p.to_first.translation).collect(),
+        })
+    }
+
+    fn part1(scanners: &AlignedScanners) -> Result<Answer> {
+        scanners.beacons.len().try_into()
     }
 
-    let mut max_dist = 0;
-    for a in &probes {
-        for b in &probes {
-            let dist = (a.pos.x - b.pos.x).abs() 
-                     + (a.pos.y - b.pos.y).abs()
-                     + (a.pos.z - b.pos.z).abs();
+    fn part2(scanners: &AlignedScanners) -> Result<Answer> {
+        let positions = &scanners.scanner_positions;
 
-            max_dist = max_dist.max(dist);
+        let mut max_dist = 0;
+        for a in positions {
+            for b in positions {
+                let dist = a.manhattan(*b);
+                max_dist = max_dist.max(dist);
+            }
         }
+
+        Ok(max_dist.into())
     }
+}
 
-    println!("Num signals {}", all_signals.len());
-    println!("Max dist {}", max_dist);
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
+
+    const EXAMPLE: &str = "\
+--- scanner 0 ---
+404,-588,-901
+528,-643,409
+-838,591,734
+390,-675,-793
+-537,-823,-458
+-485,-357,347
+-345,-311,381
+-661,-816,-575
+-876,649,763
+-618,-824,-621
+553,345,-567
+474,580,667
+-447,-329,318
+-584,868,-557
+544,-627,-890
+564,392,-477
+455,729,728
+-892,524,684
+-689,845,-530
+423,-701,434
+7,-33,-71
+630,319,-379
+443,580,662
+-789,900,-551
+459,-707,401
+
+--- scanner 1 ---
+686,422,578
+605,423,415
+515,917,-361
+-336,658,858
+95,138,22
+-476,619,847
+-340,-569,-846
+567,-361,727
+-460,603,-452
+669,-402,600
+729,430,532
+-500,-761,534
+-322,571,750
+-466,-666,-811
+-429,-592,574
+-355,545,-477
+703,-491,-529
+-328,-685,520
+413,935,-424
+-391,539,-444
+586,-435,557
+-364,-763,-893
+807,-499,-711
+755,-354,-619
+553,889,-390
+
+--- scanner 2 ---
+649,640,665
+682,-795,504
+-784,533,-524
+-644,584,-595
+-588,-843,648
+-30,6,44
+-674,560,763
+500,723,-460
+609,671,-379
+-555,-800,653
+-675,-892,-343
+697,-426,-610
+578,704,681
+493,664,-388
+-671,-858,530
+-667,343,800
+571,-461,-707
+-138,-166,112
+-889,563,-600
+646,-828,498
+640,759,510
+-630,509,768
+-681,-892,-333
+673,-379,-804
+-742,-814,-386
+577,-820,562
+
+--- scanner 3 ---
+-589,542,597
+605,-692,669
+-500,565,-823
+-660,373,557
+-458,-679,-417
+-488,449,543
+-626,468,-788
+338,-750,-386
+528,-832,-391
+562,-778,733
+-938,-730,414
+543,643,-506
+-524,371,-870
+407,773,750
+-104,29,83
+378,-903,-323
+-778,-728,485
+426,699,580
+-438,-605,-362
+-469,-447,-387
+509,732,623
+647,635,-688
+-868,-804,481
+614,-800,639
+595,780,-596
+
+--- scanner 4 ---
+727,592,562
+-293,-554,779
+441,611,-461
+-714,465,-776
+-743,427,-804
+-660,-479,-426
+832,-632,460
+927,-485,-438
+408,393,-506
+466,436,-512
+110,16,151
+-258,-428,682
+-393,719,612
+-211,-452,876
+808,-476,-593
+-575,615,604
+-485,667,467
+-680,325,-822
+-627,-443,-432
+872,-547,-609
+833,512,582
+807,604,487
+839,-516,451
+891,-625,532
+-652,-548,-490
+30,-46,-14
+";
+
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(79), Answer::Int(3621)), solve_example::<Day19>(EXAMPLE));
+    }
+    #[test]
+    fn ambiguous_rotation() {
+        // Every delta between these beacons is the same length along x and y, so swapping
+        // those axes lines them up just as well.
+        let beacons = |offset: i32| (1..=12)
+            .map(|k| format!("{},{},{}\n", k + offset, k, 2 * k))
+            .collect::<String>();
+        let input = format!("--- scanner 0 ---\n{}\n--- scanner 1 ---\n{}", beacons(0), beacons(100));
+        assert!(matches!(Day19::parse(&input), Err(AocError::NoSolution { .. })));
+    }
+}
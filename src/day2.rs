@@ -1,10 +1,11 @@
 use super::common;
-use std::path::Path;
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 
 // Returns a pair of parsed number and new cursor position
-fn parse_number(bytes: &Vec<u8>, mut cursor: usize) -> (u32, usize) {
-    let mut cur_token = Vec::new();
-    cur_token.reserve(32);
+fn parse_number(bytes: &[u8], mut cursor: usize) -> Result<(u32, usize)> {
+    let start = cursor;
+    let mut cur_token = Vec::with_capacity(32);
 
     loop {
         if cursor >= bytes.len() {
@@ -14,7 +15,7 @@ fn parse_number(bytes: &Vec<u8>, mut cursor: usize) -> (u32, usize) {
 
         match bytes[cursor] as char {
             '0'..='9' => {
-                let digit_val = bytes[cursor] - ('0' as u8);
+                let digit_val = bytes[cursor] - b'0';
                 cur_token.push(digit_val);
 
                 cursor += 1;
@@ -23,75 +24,93 @@ fn parse_number(bytes: &Vec<u8>, mut cursor: usize) -> (u32, usize) {
                 break;
             },
             _ => {
-                panic!("Unexpected char \'{}\' at position {}", 
-                       bytes[cursor] as char, cursor);
+                return Err(AocError::parse_at(bytes, cursor,
+                    format!("Unexpected char '{}'", bytes[cursor] as char)));
             },
         }
     }
- 
-    
-    let result = common::build_u32(&cur_token); 
-    (result, cursor)
+
+    if cur_token.is_empty() {
+        return Err(AocError::parse_at(bytes, cursor, "Expected a number"));
+    }
+
+    let result = common::build_num(&cur_token)
+        .ok_or_else(|| AocError::parse_at(bytes, start, "Number doesn't fit in 32 bits"))?;
+    Ok((result, cursor))
 }
 
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day2_input.txt");
-    let input_bytes = common::read_input_bytes(input_path.as_path());
+/// Returns the final depth times the horizontal position after following the commands.
+/// With use_pitch set, up and down adjust the aim instead of the depth.
+fn navigate(commands: &[(char, u32)], use_pitch: bool) -> Result<i64> {
+    let overflow = || AocError::no_solution("The submarine goes further than fits in 64 bits");
+    let add = |a: i64, b: i64| a.checked_add(b).ok_or_else(overflow);
 
-    let forward_skip = 7;
-    let down_skip = 4;
-    let up_skip = 2;
+    let mut total_z: i64 = 0;
+    let mut total_y: i64 = 0;
+    let mut pitch: i64 = 0;
+
+    for &(nav_char, mag) in commands {
+        let mag = mag as i64;
+        if use_pitch {
+            match nav_char {
+                'f' => {
+                    total_z = add(total_z, mag)?;
+                    total_y = add(total_y, mag.checked_mul(pitch).ok_or_else(overflow)?)?;
+                },
+                'u' => pitch = add(pitch, -mag)?,
+                'd' => pitch = add(pitch, mag)?,
+                _ => (),
+            }
+        } else {
+            match nav_char {
+                'f' => total_z = add(total_z, mag)?,
+                'u' => total_y = add(total_y, -mag)?,
+                'd' => total_y = add(total_y, mag)?,
+                _ => (),
+            }
+        }
+    }
 
-    let use_pitch = true;
+    total_y.checked_mul(total_z).ok_or_else(overflow)
+}
 
-    let mut total_z  = 0;
-    let mut total_y: i32 = 0;
-    let mut pitch: i32 = 0;
+fn parse_commands(input_bytes: &[u8]) -> Result<Vec<(char, u32)>> {
+    let mut commands = Vec::new();
 
     let mut i = 0;
-    loop {
+    while i < input_bytes.len() && input_bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+
+    while i < input_bytes.len() {
         let nav_char = input_bytes[i] as char; 
-        match nav_char {
-            'f' => i += forward_skip,
-            'u' => i += up_skip,
-            'd' => i += down_skip,
+        let keyword: &[u8] = match nav_char {
+            'f' => b"forward",
+            'u' => b"up",
+            'd' => b"down",
             _ => {
-                panic!("Unexpected char \'{}\' at position {}.", 
-                       nav_char, i)
+                return Err(AocError::parse_at(input_bytes, i,
+                    format!("Unexpected char '{}'", nav_char)));
             },
+        };
+
+        if !input_bytes[i..].starts_with(keyword) {
+            return Err(AocError::parse_at(input_bytes, i,
+                format!("Expected '{}'", String::from_utf8_lossy(keyword))));
         }
+        i += keyword.len();
 
-        while input_bytes[i] as char == ' ' {
+        while i < input_bytes.len() && input_bytes[i] as char == ' ' {
             i += 1;
         }
 
-        let (mag, new_i) = parse_number(&input_bytes, i);
+        let (mag, new_i) = parse_number(input_bytes, i)?;
 
         i = new_i;
-        if use_pitch {
-            match nav_char as char {
-                'f' => {
-                    total_z += mag;
-                    total_y += mag as i32 * pitch;
-                },
-                'u' => pitch -= mag as i32,
-                'd' => pitch += mag as i32,
-                _ => (),
-            }
-        } else {
-            match nav_char as char {
-                'f' => total_z += mag,
-                'u' => total_y -= mag as i32,
-                'd' => total_y += mag as i32,
-                _ => (),
-            }
-        }
+        commands.push((nav_char, mag));
         
-        if i >= input_bytes.len() {
-            break;
-        }
-
-        loop {
+        // Skip trailing whitespace so a final newline doesn't start another command.
+        while i < input_bytes.len() {
             match input_bytes[i] as char {
                 '\n' | '\r' | ' ' => i += 1,
                 _ => break,
@@ -99,6 +118,50 @@ pub fn run(root_dir: &Path) {
         }
     }
 
-    println!("Total y {} , Total z {}", total_y, total_z);
-    println!("Combined {}", total_y * total_z as i32);
-}
\ No newline at end of file
+    Ok(commands)
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Input = Vec<(char, u32)>;
+
+    fn parse(input: &str) -> Result<Vec<(char, u32)>> {
+        parse_commands(input.as_bytes())
+    }
+
+    fn part1(commands: &Vec<(char, u32)>) -> Result<Answer> {
+        Ok(navigate(commands, false)?.into())
+    }
+
+    fn part2(commands: &Vec<(char, u32)>) -> Result<Answer> {
+        Ok(navigate(commands, true)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
+
+    const EXAMPLE: &str = "\
+forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2
+";
+
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(150), Answer::Int(900)), solve_example::<Day2>(EXAMPLE));
+    }
+
+    #[test]
+    fn far_away() {
+        let commands = Day2::parse("down 3000000000\nforward 3000000000\n").unwrap();
+        assert_eq!(Answer::Int(9_000_000_000_000_000_000), Day2::part1(&commands).unwrap());
+        assert!(matches!(Day2::part2(&commands), Err(AocError::NoSolution { .. })));
+    }
+}
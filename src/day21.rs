@@ -1,5 +1,7 @@
-use std::path::Path;
-use std::collections::HashMap;
+use super::common;
+use super::common::memo::Memo;
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
 struct GameState {
@@ -13,10 +15,6 @@ struct GameState {
 
 const MAX_SCORE: u32 = 21;
 
-struct Cache {
-    entries: HashMap<GameState, (u64, u64)>
-}
-
 
 const POSSIBLE_ROLLS: [u32;27] = [
     3,
@@ -27,7 +25,7 @@ const POSSIBLE_ROLLS: [u32;27] = [
     8,8,8, 
     9,];
 
-fn solve2(state: &GameState, cache: &mut Cache) -> (u64, u64) {
+fn solve2(state: &GameState, memo: &mut Memo<GameState, (u64, u64)>) -> (u64, u64) {
     // Slightly clunky but: We computed the "other score"
     // last turn. So we check here before running the next turn.
     if state.other_score >= MAX_SCORE {
@@ -35,50 +33,124 @@ fn solve2(state: &GameState, cache: &mut Cache) -> (u64, u64) {
         if state.p2_took_turn { return (0, 1) }
     }
 
-    if let Some(entry) = cache.entries.get(state) {
-        return *entry;
-    }
+    memo.get_or_compute(*state, |memo| {
+        let mut score = (0,0);
 
-    let mut score = (0,0);
+        for d1 in &POSSIBLE_ROLLS {
+            let mut st = *state;
 
-    for d1 in &POSSIBLE_ROLLS {
-        let mut st = *state;
+            st.turn_pos += d1;
+            if st.turn_pos > 10 { st.turn_pos -= 10 }
+            st.turn_score += st.turn_pos;
 
-        st.turn_pos += d1;
-        if st.turn_pos > 10 { st.turn_pos -= 10 }
-        st.turn_score += st.turn_pos;
+            // Players take alternating turns, at each which the universes split.
+            // For slightly less branching, we switch the current turn values, rather
+            // than computing the turn based on the turn flag.
+            std::mem::swap(&mut st.turn_pos, &mut st.other_pos);
+            std::mem::swap(&mut st.turn_score, &mut st.other_score);
+            std::mem::swap(&mut st.p1_took_turn, &mut st.p2_took_turn);
 
-        // Players take alternating turns, at each which the universes split.
-        // For slightly less branching, we switch the current turn values, rather
-        // than computing the turn based on the turn flag.
-        std::mem::swap(&mut st.turn_pos, &mut st.other_pos);
-        std::mem::swap(&mut st.turn_score, &mut st.other_score);
-        std::mem::swap(&mut st.p1_took_turn, &mut st.p2_took_turn);
+            let (score1, score2) = solve2(&st, memo);
+            score.0 += score1;
+            score.1 += score2;
+        }
 
-        let (score1, score2) = solve2(&st, cache);
-        score.0 += score1;
-        score.1 += score2;
+        score
+    })
+}
+
+/// Plays to 1000 with the deterministic 100-sided die and returns
+/// the losing score multiplied by the number of rolls.
+fn play_deterministic(p1_pos: u32, p2_pos: u32) -> u32 {
+    let mut positions = [p1_pos, p2_pos];
+    let mut scores = [0, 0];
+    let mut num_rolls = 0;
+    let mut turn = 0;
+
+    loop {
+        let mut moves = 0;
+        for _ in 0..3 {
+            moves += (num_rolls % 100) + 1;
+            num_rolls += 1;
+        }
+
+        positions[turn] = (positions[turn] + moves - 1) % 10 + 1;
+        scores[turn] += positions[turn];
+
+        if scores[turn] >= 1000 {
+            return scores[1 - turn] * num_rolls;
+        }
+
+        turn = 1 - turn;
     }
+}
 
-    cache.entries.insert(*state, score);
+/// Parses the starting positions from "Player 1 starting position: 4" lines.
+fn parse_start_positions(bytes: &[u8]) -> Result<(u32, u32)> {
+    let mut positions = Vec::with_capacity(2);
 
-    score
+    for (line_idx, line) in bytes.split(|b| *b == b'\n').enumerate() {
+        let Some(colon) = line.iter().position(|b| *b == b':') else {
+            continue;
+        };
+
+        let mut cursor = colon + 1;
+        while cursor < line.len() && !line[cursor].is_ascii_digit() {
+            cursor += 1;
+        }
+
+        match common::parse_int::<u32>(line, cursor) {
+            Ok((pos, _)) if (1..=10).contains(&pos) => positions.push(pos),
+            _ => return Err(AocError::parse(line_idx + 1, cursor + 1, "Expected a position from 1 to 10")),
+        }
+    }
+
+    if positions.len() != 2 {
+        return Err(AocError::parse(1, 1, format!("Expected 2 players, found {}", positions.len())));
+    }
+    Ok((positions[0], positions[1]))
 }
 
-pub fn run(_: &Path) {    
-    let mut cache = Cache { entries: HashMap::new() };
+pub struct Day21;
 
-    let initial_state = GameState {
-        turn_pos: 1,
-        other_pos: 10,
-        turn_score: 0,
-        other_score: 0,
-        p1_took_turn: false,
-        p2_took_turn: true,
-    };
+impl Solution for Day21 {
+    type Input = (u32, u32);
 
-    let (p1_wins, p2_wins) = solve2(&initial_state, &mut cache);
+    fn parse(input: &str) -> Result<(u32, u32)> {
+        parse_start_positions(input.as_bytes())
+    }
+
+    fn part1(&(p1_start, p2_start): &(u32, u32)) -> Result<Answer> {
+        Ok(play_deterministic(p1_start, p2_start).into())
+    }
+
+    fn part2(&(p1_start, p2_start): &(u32, u32)) -> Result<Answer> {
+        let initial_state = GameState {
+            turn_pos: p1_start,
+            other_pos: p2_start,
+            turn_score: 0,
+            other_score: 0,
+            p1_took_turn: false,
+            p2_took_turn: true,
+        };
+
+        let (p1_wins, p2_wins) = solve2(&initial_state, &mut Memo::new());
+        p1_wins.max(p2_wins).try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
+
+    const EXAMPLE: &str = "\
+Player 1 starting position: 4
+Player 2 starting position: 8
+";
 
-    println!("P1 wins {} P2 wins {}", p1_wins, p2_wins);
-    println!("Max {}", p1_wins.max(p2_wins));
-}
\ No newline at end of file
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(739785), Answer::Int(444356092776315)), solve_example::<Day21>(EXAMPLE));
+    }
+}
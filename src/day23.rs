@@ -1,16 +1,25 @@
-use std::{path::Path, collections::{BinaryHeap, HashMap}};
+use super::common::search::{shortest_path, SearchState};
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 
 type Map = Vec<Vec<char>>;
 
+fn parse_input(input: &str) -> Map {
+    input.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.chars().collect())
+        .collect()
+}
+
 fn is_solved(map: &Map) -> bool {
     let room_y_top: usize = 2;
     let room_y_bot: usize = map.len() - 2;
 
-    for row in room_y_top..=room_y_bot {
-        let solved = map[row][3] == 'A' &&
-                     map[row][5] == 'B' &&
-                     map[row][7] == 'C' &&
-                     map[row][9] == 'D';
+    for row in &map[room_y_top..=room_y_bot] {
+        let solved = row[3] == 'A' &&
+                     row[5] == 'B' &&
+                     row[7] == 'C' &&
+                     row[9] == 'D';
 
         if !solved { return false; }
     }
@@ -18,13 +27,13 @@ fn is_solved(map: &Map) -> bool {
 }
 
 #[cfg(test)]
-mod SolveTests {
+mod tests {
     use super::{parse_input, is_solved};
 
     #[test]
     fn test_solved() {
         let input = "#############\n#...........#\n###A#B#C#D###\n  #A#B#C#D#  \n  #########  ";
-        let mut map = parse_input(&input);
+        let mut map = parse_input(input);
         map.insert(4, "  #A#B#C#D#  ".chars().collect());
         map.insert(4, "  #A#B#C#D#  ".chars().collect());
         assert!(is_solved(&map));
@@ -33,11 +42,25 @@ mod SolveTests {
     #[test]
     fn test_not_solved() {
         let input = "#############\n#...........#\n###B#C#B#D###\n  #A#D#C#A#  \n  #########  ";
-        let mut map = parse_input(&input);
+        let mut map = parse_input(input);
         map.insert(4, "  #D#C#B#A#  ".chars().collect());
         map.insert(4, "  #D#B#A#C#  ".chars().collect());
         assert!(!is_solved(&map));
     }
+
+    const EXAMPLE: &str = "\
+#############
+#...........#
+###B#C#B#D###
+  #A#D#C#A#
+  #########
+";
+
+    #[test]
+    fn example() {
+        use crate::solution::{solve_example, Answer};
+        assert_eq!((Answer::Int(12521), Answer::Int(44169)), solve_example::<super::Day23>(EXAMPLE));
+    }
 }
 
 fn get_room_i_for_kind(kind: char) -> Option<usize> {
@@ -99,10 +122,9 @@ fn get_next_steps(map: &Map) -> Vec<(u64, Map)>{
                 None => continue, 
         };
 
-        // If we want to fill up the room, but our room doesnt have
-        // the same kind of dude in it, we cant move there.
-        if (free_room != room_y_bot) && 
-           (map[free_room + 1][room_x] != hall[space_i]) {
+        // If we want to fill up the room, but our room has
+        // any other kind of dude in it, we cant move there.
+        if ((free_room + 1)..=room_y_bot).any(|row| map[row][room_x] != hall[space_i]) {
             continue;
         }
 
@@ -168,74 +190,103 @@ fn get_next_steps(map: &Map) -> Vec<(u64, Map)>{
     steps
 }
 
-#[derive(Clone, Default, Eq, PartialEq)]
-struct Step {
-    cost: u64,
-    map: Map
-}
+/// Moving amphipods around is a search over the whole burrow.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Burrow(Map);
+
+impl SearchState for Burrow {
+    type Cost = u64;
 
-impl Step {
-    fn new(c: u64, m: Map) -> Step {
-        Step { cost: c, map: m }
+    fn neighbors(&self, mut visit: impl FnMut(Burrow, u64)) {
+        for (cost, map) in get_next_steps(&self.0) {
+            visit(Burrow(map), cost);
+        }
     }
-}
 
-impl Ord for Step {
-    fn cmp(&self, other: &Step) -> std::cmp::Ordering {
-        other.cost.cmp(&self.cost) // Flip the order of comparison so we 
-        .then_with(|| self.map.cmp(&other.map)) // get a min-sorted heap.
+    fn is_goal(&self) -> bool {
+        is_solved(&self.0)
     }
-}
 
-impl PartialOrd for Step {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+    /// Every amphipod outside of its room has to at least walk to the top of its room.
+    fn heuristic(&self) -> u64 {
+        let mut estimate = 0;
+        for (y, row) in self.0.iter().enumerate().skip(HALL_LINE_IDX) {
+            for (x, kind) in row.iter().enumerate() {
+                let (Some(room_x), Some(cost)) = (get_room_i_for_kind(*kind), get_cost_for_kind(*kind)) else {
+                    continue;
+                };
+                if x != room_x {
+                    let steps_out = y - HALL_LINE_IDX;
+                    estimate += cost * (steps_out + x.abs_diff(room_x) + 1) as u64;
+                }
+            }
+        }
+        estimate
     }
 }
 
-fn get_min_cost_solve(_input_map: &Map) -> u64 {
-    let mut queue = BinaryHeap::new();
-    let mut visited: HashMap<Map, u64> = HashMap::new();
-
-    queue.push(Step::new(0, _input_map.clone()));
+/// Checks the map has the hallway and rooms where the solver expects them.
+fn validate_map(map: &Map) -> Result<()> {
+    if map.len() < 5 {
+        return Err(AocError::parse(map.len() + 1, 1, "Expected a hallway and at least one row of rooms"));
+    }
 
-    while let Some(cur_step) = queue.pop() {
-        if is_solved(&cur_step.map) {
-            return cur_step.cost;
-        }
+    let hall: String = map[HALL_LINE_IDX].iter().collect();
+    if hall != "#...........#" {
+        return Err(AocError::parse(HALL_LINE_IDX + 1, 1, "Expected an empty hallway of 11 spaces"));
+    }
 
-        match visited.get(&cur_step.map) {
-            Some(&existing_cost) => if cur_step.cost > existing_cost {
-                continue;
-            },
-            None => ()
+    let room_y_bot = map.len() - 2;
+    for (row_idx, row) in map.iter().enumerate().take(room_y_bot + 1).skip(ROOM_Y_TOP) {
+        for room_x in [3, 5, 7, 9] {
+            match row.get(room_x) {
+                Some('A'..='D') => {},
+                _ => return Err(AocError::parse(row_idx + 1, room_x + 1, "Expected an amphipod from A to D")),
+            }
         }
+    }
 
-        for (next_step_cost, next_step_map) in get_next_steps(&cur_step.map) {
-            let existing_cost = match visited.get(&next_step_map) {
-                Some(c) => *c,
-                None => u64::MAX 
-            };
+    let num_rows = room_y_bot + 1 - ROOM_Y_TOP;
+    for kind in ['A', 'B', 'C', 'D'] {
+        let count = map[ROOM_Y_TOP..=room_y_bot].iter()
+            .flat_map(|row| row.iter())
+            .filter(|c| **c == kind)
+            .count();
 
-            let next_cost = cur_step.cost + next_step_cost;
-            if existing_cost > next_cost {
-                visited.insert(next_step_map.clone(), next_cost);
-                queue.push(Step::new(next_cost, next_step_map));
-            }
+        if count != num_rows {
+            return Err(AocError::parse(ROOM_Y_TOP + 1, 1, format!("Expected {} amphipods of kind {}", num_rows, kind)));
         }
     }
 
-    panic!()
+    Ok(())
 }
 
-pub fn run(_: &Path) {
-    // let input = "#############\n#...........#\n###B#C#B#D###\n  #A#D#C#A#  \n  #########  ";
-    let input = "#############\n#...........#\n###B#C#A#D###\n  #B#C#D#A#  \n  #########  ";
+fn get_min_cost_solve(input_map: &Map) -> Result<u64> {
+    shortest_path(Burrow(input_map.clone()))
+        .map(|result| result.cost)
+        .ok_or_else(|| AocError::no_solution("The amphipods can't be organized"))
+}
 
-    let mut map = input.lines().map(|l| l.chars().collect()).collect::<Vec<_>>();
-    println!("{}", get_min_cost_solve(&map));
+pub struct Day23;
 
-    map.insert(3, "  #D#C#B#A#  ".chars().collect());
-    map.insert(3, "  #D#B#A#C#  ".chars().collect());
-    println!("{}", get_min_cost_solve(&map));
-}
\ No newline at end of file
+impl Solution for Day23 {
+    type Input = Map;
+
+    fn parse(input: &str) -> Result<Map> {
+        let map = parse_input(input);
+        validate_map(&map)?;
+        Ok(map)
+    }
+
+    fn part1(map: &Map) -> Result<Answer> {
+        get_min_cost_solve(map)?.try_into()
+    }
+
+    fn part2(map: &Map) -> Result<Answer> {
+        // Unfold the diagram by inserting the two extra rows between the original ones.
+        let mut map = map.clone();
+        map.insert(3, "  #D#B#A#C#  ".chars().collect());
+        map.insert(3, "  #D#C#B#A#  ".chars().collect());
+        get_min_cost_solve(&map)?.try_into()
+    }
+}
@@ -1,9 +1,9 @@
-use std::collections::HashMap;
-use std::io::BufRead;
-use std::path::Path;
+use super::common::memo::Memo;
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 
 #[derive(Clone, Copy)]
-enum Reg {
+pub enum Reg {
     X = 0,
     Y = 1,
     Z = 2,
@@ -11,139 +11,96 @@ enum Reg {
 }
 
 #[derive(Clone, Copy)]
-enum Arg {
+pub enum Arg {
     Register(Reg),
     Literal(i64)
 }
 
+/// The first operand is always the register the result goes into.
 #[derive(Clone, Copy)]
-enum Instr {
+pub enum Instr {
     SetReg(Reg),
-    Add(Arg, Arg),
-    Mul(Arg, Arg),
-    Div(Arg, Arg),
-    Mod(Arg, Arg),
-    Eql(Arg, Arg)
+    Add(Reg, Arg),
+    Mul(Reg, Arg),
+    Div(Reg, Arg),
+    Mod(Reg, Arg),
+    Eql(Reg, Arg)
 }
 
-type Registers = [i64;4];
+pub type Registers = [i64;4];
 
-fn parse_reg(tok: &str) -> Reg {
+fn parse_reg(tok: &str) -> Option<Reg> {
     match tok {
-        "x" => Reg::X,
-        "y" => Reg::Y,
-        "z" => Reg::Z,
-        "w" => Reg::W,
-        _   => panic!("Unexpected token {}", tok)
+        "x" => Some(Reg::X),
+        "y" => Some(Reg::Y),
+        "z" => Some(Reg::Z),
+        "w" => Some(Reg::W),
+        _   => None,
     }
 }
 
-fn parse_arg(tok: &str) -> Arg {
+fn parse_arg(tok: &str) -> Option<Arg> {
     match tok.parse::<i64>() {
-        Ok(n) => Arg::Literal(n),
-        _ => Arg::Register(parse_reg(tok))
+        Ok(n) => Some(Arg::Literal(n)),
+        _ => parse_reg(tok).map(Arg::Register),
     }
 }
 
-fn run_monad_block(instrs: &[Instr], input_val: i64, z_state: i64) -> Registers {
-    fn get_arg_vals(arg_0: Arg, arg_1: Arg, reg: &Registers) -> (i64, i64) {
-        match (arg_0, arg_1) {
-            (Arg::Register(r0), Arg::Register(r1)) => (reg[r0 as usize], reg[r1 as usize]),
-            (Arg::Register(r), Arg::Literal(l)) => (reg[r as usize], l),
-            (Arg::Literal(_), _) => panic!(),
-        }
-    }
+/// Runs a single instruction, with `input` being the value for `inp`. Fails on the operations
+/// that crash the ALU, and on overflow. Inlined into the block runner, which is the hot loop.
+#[inline(always)]
+fn execute(ins: Instr, input: Option<i64>, reg: &mut Registers) -> Result<()> {
+    let arg_val = |arg: Arg, reg: &Registers| match arg {
+        Arg::Register(r) => reg[r as usize],
+        Arg::Literal(l) => l,
+    };
+
+    let (r, val) = match ins {
+        Instr::SetReg(r) => {
+            let val = input.ok_or_else(|| AocError::no_solution("Ran out of input for inp"))?;
+            (r, Some(val))
+        },
+        Instr::Add(r, arg) => (r, reg[r as usize].checked_add(arg_val(arg, reg))),
+        Instr::Mul(r, arg) => (r, reg[r as usize].checked_mul(arg_val(arg, reg))),
+        Instr::Div(r, arg) => {
+            let divisor = arg_val(arg, reg);
+            if divisor == 0 {
+                return Err(AocError::no_solution("The program divides by zero"));
+            }
+            (r, reg[r as usize].checked_div(divisor))
+        },
+        Instr::Mod(r, arg) => {
+            let (a, b) = (reg[r as usize], arg_val(arg, reg));
+            if a < 0 || b <= 0 {
+                return Err(AocError::no_solution(format!("The program takes {} mod {}", a, b)));
+            }
+            (r, a.checked_rem(b))
+        },
+        Instr::Eql(r, arg) => (r, Some((reg[r as usize] == arg_val(arg, reg)) as i64)),
+    };
 
-    fn set_reg(arg: Arg, val: i64, reg: &mut Registers) {
-        match arg {
-            Arg::Register(r) => reg[r as usize] = val,
-            _ => panic!()
-        }
-    }
+    reg[r as usize] = val.ok_or_else(|| AocError::no_solution("A register overflowed"))?;
+    Ok(())
+}
 
+/// Runs one block of MONAD, which reads its digit as the block starts.
+fn run_monad_block(instrs: &[Instr], input_val: i64, z_state: i64) -> Result<Registers> {
     let mut reg = Registers::default();
     reg[Reg::Z as usize] = z_state;
     reg[Reg::W as usize] = input_val;
 
     for ins in instrs {
-        match *ins {
-            Instr::SetReg(_) => panic!(),
-            Instr::Add(r0, r1) => {
-                let (arg_0, arg_1) = get_arg_vals(r0, r1, &reg); 
-                set_reg(r0, arg_0 + arg_1, &mut reg);
-            },
-            Instr::Mul(r0, r1) => {
-                let (arg_0, arg_1) = get_arg_vals(r0, r1, &reg); 
-                set_reg(r0, arg_0 * arg_1, &mut reg);
-            },
-            Instr::Div(r0, r1) => {
-                let (arg_0, arg_1) = get_arg_vals(r0, r1, &reg); 
-                set_reg(r0, arg_0 / arg_1, &mut reg);
-            },
-            Instr::Mod(r0, r1) => {
-                let (arg_0, arg_1) = get_arg_vals(r0, r1, &reg); 
-                set_reg(r0, arg_0 % arg_1, &mut reg);
-            },
-            Instr::Eql(r0, r1) => {
-                let (arg_0, arg_1) = get_arg_vals(r0, r1, &reg); 
-                let val = if arg_0 == arg_1 { 1 } else { 0 };
-                set_reg(r0, val, &mut reg);
-            }
-        }
-    }
-    reg
-}
-
-fn transpile(instrs: &[Instr]) {
-    println!("let mut x = 0;");
-    println!("let mut y = 0;");
-    println!("let mut z = 0;");
-    println!("let mut w = 0;");
-
-    fn reg_to_str(r: Reg) -> String {
-        match r {
-            Reg::X => "x".to_string(),
-            Reg::Y => "y".to_string(),
-            Reg::Z => "z".to_string(),
-            Reg::W => "w".to_string(),
-        }
-    }
-
-    fn arg_to_str(arg: Arg) -> String {
-        match arg {
-            Arg::Register(r) => reg_to_str(r),
-            Arg::Literal(l) => l.to_string(),
-        }
-    }
-
-    for ins in instrs {
-        match *ins {
-            Instr::SetReg(r) => {
-                println!("{} = *yield_input.next().unwrap();", reg_to_str(r));
-            }
-            Instr::Add(r0, r1) => {
-                println!("{} = {} + {};", arg_to_str(r0), arg_to_str(r0), arg_to_str(r1))
-            },
-            Instr::Mul(r0, r1) => {
-                println!("{} = {} * {};", arg_to_str(r0), arg_to_str(r0), arg_to_str(r1))
-            },
-            Instr::Div(r0, r1) => {
-                println!("{} = {} / {};", arg_to_str(r0), arg_to_str(r0), arg_to_str(r1))
-            },
-            Instr::Mod(r0, r1) => {
-                println!("{} = {} % {};", arg_to_str(r0), arg_to_str(r0), arg_to_str(r1))
-            },
-            Instr::Eql(r0, r1) => {
-                println!("{} = if {} == {} {{1}} else {{0}};", arg_to_str(r0), arg_to_str(r0), arg_to_str(r1))
-            }
-        }
+        execute(*ins, None, &mut reg)?;
     }
+    Ok(reg)
 }
 
-// Map input z and code block to out z
-type Cache = HashMap<(i64, usize), Option<i64>>;
+/// Maps the z going into a code block to the digits that make the rest of the program accept.
+type Cache = Memo<(i64, usize), Option<i64>>;
 
-fn solve(code_blocks: &[Vec<Instr>], digits: &[i64], cache: &mut Cache, block_idx: usize, last_z: i64) -> Option<i64> {
+/// The cache can't hold errors, so the first one ends up in `error` and stops the search.
+fn solve(code_blocks: &[Vec<Instr>], digits: &[i64], cache: &mut Cache, error: &mut Option<AocError>,
+         block_idx: usize, last_z: i64) -> Option<i64> {
     if block_idx == code_blocks.len() {
         return if last_z == 0 {
             Some(0)
@@ -152,135 +109,209 @@ fn solve(code_blocks: &[Vec<Instr>], digits: &[i64], cache: &mut Cache, block_id
         }
     }
 
-    if let Some(&res) = cache.get(&(last_z, block_idx)) {
-        return res;
-    }
-
-    for &digit in digits {
-        let regs = run_monad_block(&code_blocks[block_idx], digit, last_z);
-        let out_z = regs[Reg::Z as usize];
-
-        match solve(code_blocks, digits, cache, block_idx + 1, out_z) {
-            Some(val) => {
-                let res = Some(val * 10 + digit);
-                cache.insert((out_z, block_idx), res);
-                return res;
-            },
-            None => (),
+    cache.get_or_compute((last_z, block_idx), |cache| {
+        for &digit in digits {
+            let out_z = match run_monad_block(&code_blocks[block_idx], digit, last_z) {
+                Ok(regs) => regs[Reg::Z as usize],
+                Err(err) => {
+                    *error = Some(err);
+                    return None;
+                },
+            };
+
+            if let Some(val) = solve(code_blocks, digits, cache, error, block_idx + 1, out_z) {
+                return Some(val * 10 + digit);
+            }
+            if error.is_some() {
+                return None;
+            }
         }
-    }
 
-    cache.insert((last_z, block_idx), None);
-
-    None
+        None
+    })
 }
 
-fn run_instructions(instrs: &[Instr], inputs: &[i64]) -> Registers {
+/// Runs a whole program, feeding `inp` instructions from `inputs` in order.
+pub fn run_instructions(instrs: &[Instr], inputs: &[i64]) -> Result<Registers> {
     let mut yield_input = inputs.iter();
     let mut reg = Registers::default();
 
-    fn get_arg_vals(arg_0: Arg, arg_1: Arg, reg: &Registers) -> (i64, i64) {
-        match (arg_0, arg_1) {
-            (Arg::Register(r0), Arg::Register(r1)) => (reg[r0 as usize], reg[r1 as usize]),
-            (Arg::Register(r), Arg::Literal(l)) => (reg[r as usize], l),
-            (Arg::Literal(_), _) => panic!(),
-        }
+    for ins in instrs {
+        let input = match ins {
+            Instr::SetReg(_) => yield_input.next().copied(),
+            _ => None,
+        };
+        execute(*ins, input, &mut reg)?;
     }
+    Ok(reg)
+}
 
-    fn set_reg(arg: Arg, val: i64, reg: &mut Registers) {
-        match arg {
-            Arg::Register(r) => reg[r as usize] = val,
-            _ => panic!()
-        }
+pub fn parse_instr(line: &str, line_idx: usize) -> Result<Instr> {
+    // Keep track of where each token starts for error reporting.
+    let mut toks = Vec::with_capacity(3);
+    let mut col = 0;
+    for tok in line.split(' ') {
+        toks.push((col, tok));
+        col += tok.len() + 1;
     }
 
-    for ins in instrs {
-        match *ins {
-            Instr::SetReg(r) => {
-                let val = yield_input.next().unwrap();
-                reg[r as usize] = *val;
-            }
-            Instr::Add(r0, r1) => {
-                let (arg_0, arg_1) = get_arg_vals(r0, r1, &reg); 
-                set_reg(r0, arg_0 + arg_1, &mut reg);
-            },
-            Instr::Mul(r0, r1) => {
-                let (arg_0, arg_1) = get_arg_vals(r0, r1, &reg); 
-                set_reg(r0, arg_0 * arg_1, &mut reg);
-            },
-            Instr::Div(r0, r1) => {
-                let (arg_0, arg_1) = get_arg_vals(r0, r1, &reg); 
-                set_reg(r0, arg_0 / arg_1, &mut reg);
-            },
-            Instr::Mod(r0, r1) => {
-                let (arg_0, arg_1) = get_arg_vals(r0, r1, &reg); 
-                set_reg(r0, arg_0 % arg_1, &mut reg);
-            },
-            Instr::Eql(r0, r1) => {
-                let (arg_0, arg_1) = get_arg_vals(r0, r1, &reg); 
-                let val = if arg_0 == arg_1 { 1 } else { 0 };
-                set_reg(r0, val, &mut reg);
-            }
-        }
+    let error_at = |tok_i: usize, message: &str| {
+        let col = toks.get(tok_i).map_or(line.len(), |(col, _)| *col);
+        AocError::parse(line_idx + 1, col + 1, message)
+    };
+
+    let op = toks[0].1;
+    let num_args = match op {
+        "inp" => 1,
+        "add" | "mul" | "div" | "mod" | "eql" => 2,
+        _ => return Err(error_at(0, &format!("Unexpected instruction '{}'", op))),
+    };
+    if toks.len() != num_args + 1 {
+        return Err(error_at(toks.len().min(num_args + 1), &format!("Expected {} argument(s)", num_args)));
     }
-    reg
-}
 
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day24_input.txt");
-    
-    let file = std::fs::File::open(input_path).unwrap();
-    let reader = std::io::BufReader::new(file);
+    let reg = parse_reg(toks[1].1).ok_or_else(|| error_at(1, "Expected a register"))?;
+    if op == "inp" {
+        return Ok(Instr::SetReg(reg));
+    }
 
-    let mut instrs = Vec::new();
+    let arg = parse_arg(toks[2].1).ok_or_else(|| error_at(2, "Expected a register or a number"))?;
+    match (op, arg) {
+        ("div", Arg::Literal(0)) => return Err(error_at(2, "Can't divide by zero")),
+        ("mod", Arg::Literal(n)) if n <= 0 => return Err(error_at(2, "Expected a positive modulus")),
+        _ => {},
+    }
 
-    for res in reader.lines() {
-        let line = match res {
-            Ok(l) => l,
-            Err(_) => continue,
-        };
+    match op {
+        "add" => Ok(Instr::Add(reg, arg)),
+        "mul" => Ok(Instr::Mul(reg, arg)),
+        "div" => Ok(Instr::Div(reg, arg)),
+        "mod" => Ok(Instr::Mod(reg, arg)),
+        "eql" => Ok(Instr::Eql(reg, arg)),
+        _   => unreachable!("Checked when counting the arguments"),
+    }
+}
+
+pub fn parse_code_blocks(input: &str) -> Result<Vec<Vec<Instr>>> {
+    let mut instrs = Vec::new();
 
-        let toks = line.split(" ").collect::<Vec<_>>();
-        assert!(toks.len() == 2 || toks.len() == 3);
-
-        match toks[0] {
-            "inp" => {
-                let reg = parse_reg(toks[1]);
-                instrs.push(Instr::SetReg(reg));
-            },
-            "add" => instrs.push(Instr::Add(parse_arg(toks[1]), parse_arg(toks[2]))),
-            "mul" => instrs.push(Instr::Mul(parse_arg(toks[1]), parse_arg(toks[2]))),
-            "div" => instrs.push(Instr::Div(parse_arg(toks[1]), parse_arg(toks[2]))),
-            "mod" => instrs.push(Instr::Mod(parse_arg(toks[1]), parse_arg(toks[2]))),
-            "eql" => instrs.push(Instr::Eql(parse_arg(toks[1]), parse_arg(toks[2]))),
-            _   => panic!("Unexpected token {}", toks[0])
+    for (line_idx, line) in input.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
         }
+
+        instrs.push(parse_instr(line, line_idx)?);
     }
 
     // MONAD has 14 blocks that operate on each input digit,
     // carrying over state from the last block in the z register.
     // We can solve this individually, if we pass along the state.
     let mut code_blocks = instrs.split(|i| {
-        match i {
-            Instr::SetReg(_) => true,
-            _ => false
-        }
+        matches!(i, Instr::SetReg(_))
     }).map(|i| i.to_vec()).collect::<Vec<_>>();
 
     code_blocks.remove(0);
 
-    let mut cache = HashMap::new(); 
+    if code_blocks.len() != 14 {
+        return Err(AocError::parse(1, 1, format!("Expected 14 input blocks, found {}", code_blocks.len())));
+    }
+
+    Ok(code_blocks)
+}
+
+/// Finds the model number trying digits in the given order first.
+pub fn find_model_number(code_blocks: &[Vec<Instr>], digits: &[i64]) -> Result<i64> {
+    // The cache only stays valid for a fixed digit order, so every search gets its own.
+    let mut error = None;
+    let found = solve(code_blocks, digits, &mut Memo::new(), &mut error, 0, 0);
+    if let Some(err) = error {
+        return Err(err);
+    }
+    let reversed = found.ok_or_else(|| AocError::no_solution("MONAD accepts no model number"))?;
+
+    // solve() builds the number up starting from the last digit, so flip it around.
+    let mut remaining = reversed;
+    let mut number = 0;
+    while remaining > 0 {
+        number = number * 10 + remaining % 10;
+        remaining /= 10;
+    }
+    Ok(number)
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    type Input = Vec<Vec<Instr>>;
+
+    fn parse(input: &str) -> Result<Vec<Vec<Instr>>> {
+        parse_code_blocks(input)
+    }
+
+    fn part1(code_blocks: &Vec<Vec<Instr>>) -> Result<Answer> {
+        Ok(find_model_number(code_blocks, &(1..=9).rev().collect::<Vec<_>>())?.into())
+    }
+
+    fn part2(code_blocks: &Vec<Vec<Instr>>) -> Result<Answer> {
+        Ok(find_model_number(code_blocks, &(1..=9).collect::<Vec<_>>())?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The puzzle has no example MONAD, so check the ALU on the example programs instead.
+    fn run(program: &str, inputs: &[i64]) -> Registers {
+        let instrs = program.lines().enumerate()
+            .map(|(line_idx, line)| parse_instr(line, line_idx).unwrap())
+            .collect::<Vec<_>>();
+        run_instructions(&instrs, inputs).unwrap()
+    }
+
+    #[test]
+    fn negate() {
+        let reg = run("inp x\nmul x -1", &[7]);
+        assert_eq!(-7, reg[Reg::X as usize]);
+    }
+
+    #[test]
+    fn three_times_larger() {
+        let program = "inp z\ninp x\nmul z 3\neql z x";
+        assert_eq!(1, run(program, &[2, 6])[Reg::Z as usize]);
+        assert_eq!(0, run(program, &[2, 5])[Reg::Z as usize]);
+    }
 
-    let biggest = solve(&code_blocks, &(1..=9).rev().collect::<Vec<_>>(), &mut cache, 0, 0).unwrap();
-    for d in biggest.to_string().chars().rev() {
-        print!("{}", d);
+    #[test]
+    fn to_binary() {
+        let program = "\
+inp w
+add z w
+mod z 2
+div w 2
+add y w
+mod y 2
+div w 2
+add x w
+mod x 2
+div w 2
+mod w 2";
+        let reg = run(program, &[13]);
+        let bits = [Reg::W, Reg::X, Reg::Y, Reg::Z].map(|r| reg[r as usize]);
+        assert_eq!([1, 1, 0, 1], bits);
     }
-    println!();
-    
 
-    let smallest = solve(&code_blocks, &(1..=9).collect::<Vec<_>>(), &mut cache, 0, 0).unwrap();
-    for d in smallest.to_string().chars().rev() {
-        print!("{}", d);
+    #[test]
+    fn crashes() {
+        assert!(parse_instr("div z 0", 0).is_err());
+        assert!(parse_instr("mod x -3", 0).is_err());
+
+        let instrs = ["inp x", "inp y", "div x y"].iter().enumerate()
+            .map(|(line_idx, line)| parse_instr(line, line_idx).unwrap())
+            .collect::<Vec<_>>();
+        assert!(run_instructions(&instrs, &[5, 0]).is_err());
+        assert!(run_instructions(&instrs, &[5]).is_err());
+        assert_eq!(2, run_instructions(&instrs, &[5, 2]).unwrap()[Reg::X as usize]);
     }
-    println!();
 }
@@ -1,80 +1,113 @@
-use std::{path::Path, io::BufRead};
-
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day25_input.txt");
-    let file = std::fs::File::open(input_path).unwrap();
-    let reader = std::io::BufReader::new(file);
-
-    let mut map = reader.lines()
-        .map(|r| { 
-            match r {
-                Ok(l) => l.chars().collect(),
-                _ => Vec::new() 
-            }})
-        .filter(|l| !l.is_empty())
-        .collect::<Vec<_>>();
-
-    let width = map[0].len();
-    let height = map.len();
-
-    let mut east_moves = Vec::new(); 
-    let mut south_moves = Vec::new(); 
-    let mut num_steps = 0;
+use super::common::bitset::DynBitSet;
+use super::common::grid::{Edges, Grid};
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
+use std::collections::HashSet;
 
-    loop {
-        num_steps += 1;
-        let mut moves_this_step = 0;
-
-        for y in 0..height {
-            for x in 0..width {
-                match map[y][x] {
-                    '>' => {
-                        let next_x = (x + 1) % width;
-                        if map[y][next_x] == '.' {
-                            east_moves.push(((x, y), (next_x, y)));
-                        }
-                    }, 
-                    _ => (),
-                }
-            }
-        }
+type Map = Grid<char>;
 
-        for mov in &east_moves {
-            map[mov.0.1][mov.0.0] = '.';
-            map[mov.1.1][mov.1.0] = '>';
-        }
-        moves_this_step += east_moves.len();
-        east_moves.clear();
-
-        for y in 0..height {
-            for x in 0..width {
-                match map[y][x] {
-                    'v' => {
-                        let next_y = (y + 1) % height;
-                        if map[next_y][x] == '.' {
-                            south_moves.push(((x, y), (x, next_y)));
-                        }
-                    }, 
-                    _ => (),
-                }
-            }
-        }
+fn parse_map(input: &str) -> Result<Map> {
+    Grid::parse_with(input, |c| ">v.".contains(c).then_some(c))
+}
+
+/// Moves everyone in the herd with a free spot ahead, all at once. Returns how many moved.
+fn move_herd(herd: &mut DynBitSet, other: &DynBitSet, ahead: impl Fn(usize) -> usize,
+             moves: &mut Vec<(usize, usize)>) -> usize {
+    moves.clear();
+    moves.extend(herd.iter()
+        .map(|idx| (idx, ahead(idx)))
+        .filter(|(_, to)| !herd.contains(*to) && !other.contains(*to)));
+
+    for (from, to) in moves.iter() {
+        herd.remove(*from);
+        herd.insert(*to);
+    }
+    moves.len()
+}
 
-        for mov in &south_moves {
-            map[mov.0.1][mov.0.0] = '.';
-            map[mov.1.1][mov.1.0] = 'v';
+fn steps_until_rest(map: &Map) -> Result<u32> {
+    // Each herd is a set of cell indices.
+    let herd = |kind: char| map.iter().enumerate()
+        .filter(|(_, cell)| **cell == kind)
+        .map(|(idx, _)| idx)
+        .collect::<DynBitSet>();
+    let mut east = herd('>');
+    let mut south = herd('v');
+
+    // The sea floor wraps around at the edges.
+    let step = |idx: usize, offset: (isize, isize)| {
+        let (x, y) = map.neighbor_pos(map.xy(idx), offset, &Edges::Wrap).unwrap();
+        map.idx(x, y)
+    };
+
+    let mut moves = Vec::new();
+    let mut num_steps = 0;
+    // Herds going around in circles come back to a state they were in before.
+    let mut seen = HashSet::new();
+
+    // East moving all check if they can move at the same time, only do if they can at that moment
+    // Then south facing move, all check at the same time as well, but they see the moves the east
+    // facing made before
+    loop {
+        if !seen.insert((east.clone(), south.clone())) {
+            return Err(AocError::no_solution(format!("The sea cucumbers keep moving in a cycle after {} steps", num_steps)));
         }
-        moves_this_step += south_moves.len();
-        south_moves.clear();
+        num_steps += 1;
+        let mut moves_this_step = move_herd(&mut east, &south, |idx| step(idx, (1, 0)), &mut moves);
+        moves_this_step += move_herd(&mut south, &east, |idx| step(idx, (0, 1)), &mut moves);
 
         if moves_this_step == 0 {
             break;
         }
     }
 
-    println!("Reached rest after {} steps", num_steps);
+    Ok(num_steps)
+}
 
-    // East moving all check if they can move at the same time, only do if they can at that moment
-    // Then south facing move, all check at the same time as well, but they see the moves the east
-    // facing made before
-}
\ No newline at end of file
+pub struct Day25;
+
+impl Solution for Day25 {
+    type Input = Map;
+
+    fn parse(input: &str) -> Result<Map> {
+        parse_map(input)
+    }
+
+    fn part1(map: &Map) -> Result<Answer> {
+        Ok(steps_until_rest(map)?.into())
+    }
+
+    // Day 25 only has a single puzzle, the second star is free.
+    fn part2(_: &Map) -> Result<Answer> {
+        Ok(Answer::None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
+
+    const EXAMPLE: &str = "\
+v...>>.vv>
+.vv>>.vv..
+>>.>v>...v
+>>v>>.>.v.
+v>v.vv.v..
+>.>>..v...
+.vv..>.>v.
+v.v..>>v.v
+....v..v.>
+";
+
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(58), Answer::None), solve_example::<Day25>(EXAMPLE));
+    }
+
+    #[test]
+    fn never_settling() {
+        let map = parse_map(">>..v\n.....\n").unwrap();
+        assert!(matches!(steps_until_rest(&map), Err(AocError::NoSolution { .. })));
+    }
+}
@@ -1,5 +1,6 @@
-use super::common;
-use std::path::Path;
+use super::common::bitset::BitSet;
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 
 #[derive(Clone, Copy, Default)]
 struct DigitCounts {
@@ -7,28 +8,19 @@ struct DigitCounts {
     one: u32,
 }
 
-const NUM_BIN_DIGITS : usize = 12; // Each binary number in our input has 12 digits.
+impl DigitCounts {
+    fn count(values: &[BitSet], digit: usize) -> DigitCounts {
+        let one = values.iter().filter(|value| value.contains(digit)).count() as u32;
+        DigitCounts { zero: values.len() as u32 - one, one }
+    }
+}
 
-fn fliter_and_reduce(input: &Vec<u32>, get_condition: fn(&DigitCounts) -> bool) -> Vec<u32> {
-    let mut values = input.clone();
+fn fliter_and_reduce(input: &[BitSet], num_digits: usize, get_condition: fn(&DigitCounts) -> bool) -> Vec<BitSet> {
+    let mut values = input.to_vec();
     
-    for i in (0..(NUM_BIN_DIGITS)).rev() {
-        let mut count = DigitCounts::default();
-
-        for value in &values {
-            let bit_set = (*value & (1 << i)) != 0;
-            match bit_set {
-                true => count.one += 1,
-                false => count.zero += 1,
-            }
-        }
-
-        let select_ones = get_condition(&count);
-
-        values = values.iter().filter(|x| {
-            let bit_set = (*x & (1 << i)) != 0;
-            select_ones == bit_set 
-        }).map(|x| *x).collect();
+    for i in (0..num_digits).rev() {
+        let select_ones = get_condition(&DigitCounts::count(&values, i));
+        values.retain(|value| value.contains(i) == select_ones);
 
         if values.len() == 1 {
             break;
@@ -38,74 +30,113 @@ fn fliter_and_reduce(input: &Vec<u32>, get_condition: fn(&DigitCounts) -> bool)
     values
 }
 
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day3_input.txt");
-    let input_bytes = common::read_input_bytes(input_path.as_path());
+pub struct Report {
+    values: Vec<BitSet>,
+    num_digits: usize,
+}
 
-    let mut all_counts: Vec<DigitCounts> = Vec::new();
-    all_counts.resize(NUM_BIN_DIGITS, Default::default());
+pub struct Day3;
 
-    let mut parsed_values: Vec<u32> = Vec::new();
+impl Solution for Day3 {
+    type Input = Report;
 
-    let mut cursor = 0;
-    loop {
-        if cursor >= input_bytes.len() {
-            break;
+    fn parse(input: &str) -> Result<Report> {
+        let input_bytes = input.as_bytes();
+
+        // All binary numbers in the input have the same number of digits.
+        let num_digits = input_bytes.iter()
+            .position(|b| b.is_ascii_whitespace())
+            .unwrap_or(input_bytes.len());
+
+        if num_digits > 32 {
+            return Err(AocError::parse(1, 1, "Expected at most 32 binary digits per line"));
         }
 
-        let mut parsed_value: u32 = 0;
-
-        for digit_n in 0..NUM_BIN_DIGITS {
-            match input_bytes[cursor + digit_n] as char {
-                '0' => {
-                    all_counts[digit_n].zero += 1;
-                },
-                '1' => {
-                    all_counts[digit_n].one += 1;
-
-                    // We're reading the numbers back to front, so offset
-                    // for conversion to decimal.
-                    let digit_pos = NUM_BIN_DIGITS - 1 - digit_n;
-                    parsed_value += 2u32.pow(digit_pos as u32); 
-                },
-                _ => panic!("Unexpected char at pos {}", cursor + digit_n),
+        let mut parsed_values = Vec::new();
+
+        let mut cursor = 0;
+        loop {
+            if cursor >= input_bytes.len() {
+                break;
             }
-        }
 
-        parsed_values.push(parsed_value);
-        cursor += NUM_BIN_DIGITS + 2; // Skip to start of next line.
-    }
+            let mut parsed_value = BitSet::new();
+
+            for digit_n in 0..num_digits {
+                match input_bytes.get(cursor + digit_n).map(|b| *b as char) {
+                    Some('0') => (),
+                    Some('1') => {
+                        // The first digit is the most significant bit.
+                        parsed_value.insert(num_digits - 1 - digit_n);
+                    },
+                    _ => {
+                        return Err(AocError::parse_at(input_bytes, cursor + digit_n,
+                            format!("Expected {} binary digits", num_digits)));
+                    },
+                }
+            }
 
-    let mut gamma = 0; 
-    let mut epsilon = 0;
-    let mut digit = 0;
-    for count in all_counts.iter().rev() {
-        if count.one > count.zero {
-            gamma += 2u32.pow(digit); 
-        } else {
-            epsilon += 2u32.pow(digit); 
+            parsed_values.push(parsed_value);
+            cursor += num_digits;
+
+            // Skip to start of next line, which may be terminated by \n or \r\n.
+            while cursor < input_bytes.len() && input_bytes[cursor].is_ascii_whitespace() {
+                cursor += 1;
+            }
         }
 
-        digit += 1;
+        Ok(Report { values: parsed_values, num_digits })
     }
 
-    println!("Gamma {}, Epsilon {}, Power: {}", gamma, epsilon, gamma * epsilon);
+    fn part1(report: &Report) -> Result<Answer> {
+        let digits = (0..report.num_digits).collect::<BitSet>();
+        let gamma = digits.iter().filter(|digit| {
+            let count = DigitCounts::count(&report.values, *digit);
+            count.one > count.zero
+        }).collect::<BitSet>();
+        let epsilon = digits & !gamma;
 
-    let oxygen_values = fliter_and_reduce(&parsed_values, |count| {
-        count.one > count.zero || count.one == count.zero
-    });
-    
-    let co2_values = fliter_and_reduce(&parsed_values, |count| {
-        count.one < count.zero
-    });
-
-    for v in &oxygen_values {
-        println!("Oxygen {}", v);
+        (gamma.words()[0] * epsilon.words()[0]).try_into()
     }
 
-    for v in &co2_values {
-        println!("CO2 {}", v);
+    fn part2(report: &Report) -> Result<Answer> {
+        let oxygen_values = fliter_and_reduce(&report.values, report.num_digits, |count| {
+            count.one >= count.zero
+        });
+        
+        let co2_values = fliter_and_reduce(&report.values, report.num_digits, |count| {
+            count.one < count.zero
+        });
+
+        match (oxygen_values.first(), co2_values.first()) {
+            (Some(oxygen), Some(co2)) => (oxygen.words()[0] * co2.words()[0]).try_into(),
+            _ => Err(AocError::no_solution("No values left after filtering the report")),
+        }
     }
+}
 
-    println!("Life support {}", oxygen_values[0] * co2_values[0]);
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
+
+    const EXAMPLE: &str = "\
+00100
+11110
+10110
+10111
+10101
+01111
+00111
+11100
+10000
+11001
+00010
+01010
+";
+
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(198), Answer::Int(230)), solve_example::<Day3>(EXAMPLE));
+    }
+}
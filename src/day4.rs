@@ -1,6 +1,7 @@
 use super::common;
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 use std::fmt;
-use std::path::Path;
 
 const BOARD_WIDTH: usize = 5;
 
@@ -20,25 +21,32 @@ impl fmt::Display for Board {
     }
 }
 
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day4_input.txt");
-    let input_bytes = common::read_input_bytes(input_path.as_path());
+pub struct Bingo {
+    drawn_numbers: Vec<u32>,
+    boards: Vec<Board>,
+}
 
+fn parse_bingo(input_bytes: &[u8]) -> Result<Bingo> {
     let mut cursor = 0;
     let drawn_numbers = {
         let mut values: Vec<u32> = Vec::new();
 
-        let mut cur_token = Vec::new();
-        cur_token.reserve(32);
-    
+        let mut cur_token = Vec::with_capacity(32);
+
         loop {
+            if cursor >= input_bytes.len() {
+                return Err(AocError::parse_at(input_bytes, cursor, "Expected boards after the drawn numbers"));
+            }
+
             match input_bytes[cursor] as char {
                 '0'..='9' => {
-                    let digit_val = input_bytes[cursor] - ('0' as u8);
+                    let digit_val = input_bytes[cursor] - b'0';
                     cur_token.push(digit_val);    
                 },
                 ',' | '\n' | '\r' | ' ' => {
-                    let result = common::build_u32(&cur_token); 
+                    let result = common::build_num(&cur_token).ok_or_else(|| {
+                        AocError::parse_at(input_bytes, cursor - cur_token.len(), "Number doesn't fit in 32 bits")
+                    })?;
                     values.push(result);
                     cur_token.clear();
 
@@ -48,8 +56,8 @@ pub fn run(root_dir: &Path) {
                     }
                 },
                 _ => {
-                    panic!("Unexpected char \'{}\' at position {}", 
-                           input_bytes[cursor] as char, cursor);
+                    return Err(AocError::parse_at(input_bytes, cursor,
+                        format!("Unexpected char '{}'", input_bytes[cursor] as char)));
                 },
             }
 
@@ -61,7 +69,7 @@ pub fn run(root_dir: &Path) {
 
 
     // Scan forward until we hit the board data.
-    while !('0'..':').contains(&(input_bytes[cursor] as char)) {
+    while cursor < input_bytes.len() && !('0'..':').contains(&(input_bytes[cursor] as char)) {
         cursor += 1;
     }
 
@@ -96,10 +104,9 @@ pub fn run(root_dir: &Path) {
         let mut boards: Vec<Board> = Vec::new();
         let mut builder = BoardBuilder::default();
 
-        let mut cur_token = Vec::new();
-        cur_token.reserve(32);
-        
-        'outer: loop {
+        let mut cur_token = Vec::with_capacity(32);
+    
+        'outer: while cursor < input_bytes.len() {
             'parse_num: loop {
                 if cursor >= input_bytes.len() {
                     break 'parse_num; // We've reached EOF.
@@ -107,7 +114,7 @@ pub fn run(root_dir: &Path) {
 
                 match input_bytes[cursor] as char {
                     '0'..='9' => {
-                        let digit_val = input_bytes[cursor] - ('0' as u8);
+                        let digit_val = input_bytes[cursor] - b'0';
                         cur_token.push(digit_val);  
                         cursor += 1;
                     },
@@ -115,13 +122,15 @@ pub fn run(root_dir: &Path) {
                         break 'parse_num;
                     },
                     _ => {
-                        panic!("Unexpected char \'{}\' at position {}", 
-                               input_bytes[cursor] as char, cursor);
+                        return Err(AocError::parse_at(input_bytes, cursor,
+                            format!("Unexpected char '{}'", input_bytes[cursor] as char)));
                     },  
                 }
             }
 
-            let result = common::build_u32(&cur_token); 
+            let result = common::build_num(&cur_token).ok_or_else(|| {
+                AocError::parse_at(input_bytes, cursor - cur_token.len(), "Number doesn't fit in 32 bits")
+            })?;
             builder.push(result);
             cur_token.clear();
 
@@ -147,42 +156,54 @@ pub fn run(root_dir: &Path) {
             } 
         }
 
+        if builder.cur_idx != 0 {
+            return Err(AocError::parse_at(input_bytes, cursor,
+                format!("Incomplete board, expected {} numbers", BOARD_WIDTH * BOARD_WIDTH)));
+        }
+
+        if boards.is_empty() {
+            return Err(AocError::parse_at(input_bytes, cursor, "Expected at least one board"));
+        }
+
         boards
     };
 
-    const INVALID_IDX : i32 = -1;
+    Ok(Bingo { drawn_numbers, boards })
+}
 
-    struct BoardSolver {
-        highest_col_idx: [i32; 5],
-        highest_row_idx: [i32; 5],
-        incomplete_rows_mask: u8,
-        incomplete_cols_mask: u8,
-    }
+const INVALID_IDX : i32 = -1;
 
-    impl BoardSolver {
-        pub fn new() -> BoardSolver {
-            BoardSolver {        
-                highest_col_idx: [INVALID_IDX; 5],
-                highest_row_idx: [INVALID_IDX; 5],
-                incomplete_rows_mask: 0,
-                incomplete_cols_mask: 0,
-            }
+struct BoardSolver {
+    highest_col_idx: [i32; 5],
+    highest_row_idx: [i32; 5],
+    incomplete_rows_mask: u8,
+    incomplete_cols_mask: u8,
+}
+
+impl BoardSolver {
+    fn new() -> BoardSolver {
+        BoardSolver {        
+            highest_col_idx: [INVALID_IDX; 5],
+            highest_row_idx: [INVALID_IDX; 5],
+            incomplete_rows_mask: 0,
+            incomplete_cols_mask: 0,
         }
     }
+}
 
+/// Returns the index of the drawn number that completes each board.
+fn find_solve_idxs(bingo: &Bingo) -> Vec<i32> {
     let no_solution_mask = 31; // 2^5 -1
 
     let mut earliest_solve_idxs = Vec::new();
 
-    for board in &boards {
+    for board in &bingo.boards {
         let mut solver = BoardSolver::new();
 
-        'board_inner: for row_i in 0..BOARD_WIDTH {
-            let row = &board.rows[row_i];
-
-            for col_i in 0..BOARD_WIDTH {
-                match drawn_numbers.iter().
-                position(|x| *x == row[col_i]) {
+        'board_inner: for (row_i, row) in board.rows.iter().enumerate() {
+            for (col_i, value) in row.iter().enumerate() {
+                match bingo.drawn_numbers.iter().
+                position(|x| x == value) {
                     Some(idx) => {
                         let row_max = &mut solver.highest_row_idx[row_i];
                         *row_max = i32::max(*row_max, idx as i32);
@@ -209,29 +230,34 @@ pub fn run(root_dir: &Path) {
         
         let mut lowest_drawn_idx = i32::MAX;
 
-        for i in 0..BOARD_WIDTH {
-            lowest_drawn_idx = lowest_drawn_idx.min(solver.highest_row_idx[i]);
-            lowest_drawn_idx = lowest_drawn_idx.min(solver.highest_col_idx[i]);
+        for (row_max, col_max) in solver.highest_row_idx.iter().zip(&solver.highest_col_idx) {
+            lowest_drawn_idx = lowest_drawn_idx.min(*row_max);
+            lowest_drawn_idx = lowest_drawn_idx.min(*col_max);
         }
 
         earliest_solve_idxs.push(lowest_drawn_idx);
     }
 
-    let pick_earliest_win = false; 
+    earliest_solve_idxs
+}
+
+/// Returns the score of either the first or the last board to win.
+fn final_score(bingo: &Bingo, pick_earliest_win: bool) -> Result<u32> {
+    let earliest_solve_idxs = find_solve_idxs(bingo);
 
     let final_solving_i = {
         if pick_earliest_win {
             let mut earliest_solve_i = 0;
-            for i in 0..earliest_solve_idxs.len() {
-                if earliest_solve_idxs[i] < earliest_solve_idxs[earliest_solve_i] {
+            for (i, solve_idx) in earliest_solve_idxs.iter().enumerate() {
+                if *solve_idx < earliest_solve_idxs[earliest_solve_i] {
                     earliest_solve_i = i;
                 }
             }
             earliest_solve_i
         } else {
             let mut last_solve_i = 0;
-            for i in 0..earliest_solve_idxs.len() {
-                if earliest_solve_idxs[i] > earliest_solve_idxs[last_solve_i] {
+            for (i, solve_idx) in earliest_solve_idxs.iter().enumerate() {
+                if *solve_idx > earliest_solve_idxs[last_solve_i] {
                     last_solve_i = i;
                 }
             }
@@ -239,20 +265,73 @@ pub fn run(root_dir: &Path) {
         }
     };
     
-    let solved_board = &boards[final_solving_i];
+    if earliest_solve_idxs[final_solving_i] == i32::MAX {
+        return Err(AocError::no_solution("The board never wins with the drawn numbers"));
+    }
+
+    let solved_board = &bingo.boards[final_solving_i];
     let idx_of_final_drawn = earliest_solve_idxs[final_solving_i] as usize;
-    let final_drawn_numbers = &drawn_numbers[..idx_of_final_drawn + 1];
+    let final_drawn_numbers = &bingo.drawn_numbers[..idx_of_final_drawn + 1];
 
     let mut score = 0;
-    for row_i in 0..BOARD_WIDTH {
-        for col_i in 0..BOARD_WIDTH {
-            let cur_val = solved_board.rows[row_i][col_i];
-            if !final_drawn_numbers.contains(&cur_val) {
+    for row in &solved_board.rows {
+        for cur_val in row {
+            if !final_drawn_numbers.contains(cur_val) {
                 score += cur_val;
             } 
         }
     }
 
-    let final_number_i = earliest_solve_idxs[final_solving_i] as usize;
-    println!("Final score {}", score * drawn_numbers[final_number_i]);
-}
\ No newline at end of file
+    Ok(score * bingo.drawn_numbers[idx_of_final_drawn])
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input = Bingo;
+
+    fn parse(input: &str) -> Result<Bingo> {
+        parse_bingo(input.as_bytes())
+    }
+
+    fn part1(bingo: &Bingo) -> Result<Answer> {
+        Ok(final_score(bingo, true)?.into())
+    }
+
+    fn part2(bingo: &Bingo) -> Result<Answer> {
+        Ok(final_score(bingo, false)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
+
+    const EXAMPLE: &str = "\
+7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+";
+
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(4512), Answer::Int(1924)), solve_example::<Day4>(EXAMPLE));
+    }
+}
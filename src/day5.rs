@@ -1,10 +1,11 @@
-use crate::common::parse_num;
-
-use super::common;
-use std::{path::Path, fmt};
+use super::common::{Scanner, SparseGrid};
+use crate::scan;
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
+use std::fmt;
 
 #[derive(Default, Clone, Copy)]
-struct LineSegment {
+pub struct LineSegment {
     x1: i32,
     y1: i32,
     x2: i32,
@@ -17,119 +18,103 @@ impl fmt::Display for LineSegment {
     }
 }
 
-fn is_numeric(ascii_byte: u8) -> bool {
-    ('1'..='9').contains(&(ascii_byte as char))
-}
-
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day5_input.txt");
-    let bytes = common::read_input_bytes(input_path.as_path());
+/// Returns the number of grid cells covered by at least two lines.
+fn count_intersections(lines: &[LineSegment]) -> u32 {
+    let mut grid: SparseGrid<u16> = SparseGrid::new(0);
 
-    let mut lines: Vec<LineSegment> = Vec::new();
-    let mut cursor = 0;
+    for line in lines {
+        // Lines are horizontal, vertical or diagonal, so step by at most one in each direction.
+        let x_dir = (line.x2 - line.x1).signum();
+        let y_dir = (line.y2 - line.y1).signum();
 
-    let mut token_buf = Vec::new();
-    token_buf.reserve(32);
+        let mut o_x = line.x1;
+        let mut o_y = line.y1;
 
-    let mut line_builder = Vec::new();
-    line_builder.reserve(4);
+        loop  {
+            *grid.get_mut(o_x as i64, o_y as i64) += 1;
 
-    while cursor < bytes.len() {     
-        let (parsed_num, new_cursor) = parse_num(&bytes, &mut token_buf, cursor);
-        match parsed_num {
-            Some(num) => {
-                cursor = new_cursor;
-                line_builder.push(num);
-            },
-            None => {
-                panic!("Unexpectedly failed to parse number at pos {}", cursor)
+            if (o_x == line.x2) && (o_y == line.y2) {
+                break;
             }
-        }
-
-        if line_builder.len() == 4 {
-            lines.push(LineSegment {
-                x1: line_builder[0] as i32,
-                y1: line_builder[1] as i32,
-                x2: line_builder[2] as i32,
-                y2: line_builder[3] as i32,
-            });
-
-            line_builder.clear();
-        }
 
-        while cursor < bytes.len() && (!is_numeric(bytes[cursor])) {
-            cursor += 1;
+            o_x += x_dir;
+            o_y += y_dir;
         }
     }
 
-    // lines = lines.into_iter().filter(|line| {
-    //             (line.x1 == line.x2) || (line.y1 == line.y2)
-    //     }).collect();
-
-    let mut max_x = 0;
-    let mut max_y = 0;
+    grid.iter().filter(|(_, cell)| **cell > 1).count() as u32
+}
 
-    for i in 0..lines.len() {
-        max_x = i32::max(max_x, i32::max(lines[i].x1, lines[i].x2));
-        max_y = i32::max(max_y, i32::max(lines[i].y1, lines[i].y2));
+fn parse_lines(bytes: &[u8]) -> Result<Vec<LineSegment>> {
+    let mut lines = Vec::new();
+    let mut scanner = Scanner::new(bytes);
+
+    scanner.skip_ws();
+    while !scanner.is_at_end() {
+        let line = scanner.line();
+        let [x1, y1, x2, y2]: [i32; 4] = scan!(scanner, "{},{} -> {},{}")?;
+        // Anything else would never step onto its end point.
+        if x1 != x2 && y1 != y2 && x1.abs_diff(x2) != y1.abs_diff(y2) {
+            return Err(AocError::parse(line, 1, "Expected a horizontal, vertical or 45 degree line"));
+        }
+        lines.push(LineSegment { x1, y1, x2, y2 });
+        scanner.skip_ws();
     }
 
-    let mut grid: Vec<u16> = Vec::new();
-    grid.resize(((max_x + 1) * (max_y + 1)) as usize, 0);
-
-    let mut inc_grid_cell = |x: i32, y: i32| {
-        let idx = x + (max_x * y);
-        grid[idx as usize] += 1;
-    };
-
-    for line in &lines {
-        if line.x1 == line.x2 {
-            let min = i32::min(line.y1, line.y2);
-            let max = i32::max(line.y1, line.y2);
+    Ok(lines)
+}
 
-            for i in min..=max {
-                inc_grid_cell(line.x1, i);
-            }
-        } else if line.y1 == line.y2 {
-            let min = i32::min(line.x1, line.x2);
-            let max = i32::max(line.x1, line.x2);
+pub struct Day5;
 
-            for i in min..=max {
-                inc_grid_cell(i, line.y1);
-            }
-        } else {
-            let x_dir = match line.x2 > line.x1 {
-                true => 1,
-                false => -1,
-            };
+impl Solution for Day5 {
+    type Input = Vec<LineSegment>;
 
-            let y_dir = match line.y2 > line.y1 {
-                true => 1,
-                false => -1,
-            };
+    fn parse(input: &str) -> Result<Vec<LineSegment>> {
+        parse_lines(input.as_bytes())
+    }
 
-            let mut o_x = line.x1;
-            let mut o_y = line.y1;
+    fn part1(lines: &Vec<LineSegment>) -> Result<Answer> {
+        let straight_lines = lines.iter()
+            .filter(|line| (line.x1 == line.x2) || (line.y1 == line.y2))
+            .copied()
+            .collect::<Vec<_>>();
 
-            loop  {
-                inc_grid_cell(o_x, o_y);
+        Ok(count_intersections(&straight_lines).into())
+    }
 
-                if (o_x == line.x2) && (o_y == line.y2) {
-                    break;
-                }
+    fn part2(lines: &Vec<LineSegment>) -> Result<Answer> {
+        Ok(count_intersections(lines).into())
+    }
+}
 
-                o_x += x_dir;
-                o_y += y_dir;
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
+
+    const EXAMPLE: &str = "\
+0,9 -> 5,9
+8,0 -> 0,8
+9,4 -> 3,4
+2,2 -> 2,1
+7,0 -> 7,4
+6,4 -> 2,0
+0,9 -> 2,9
+3,4 -> 1,4
+0,0 -> 8,8
+5,5 -> 8,2
+";
+
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(5), Answer::Int(12)), solve_example::<Day5>(EXAMPLE));
     }
 
-    let mut num_intersections = 0;
-    for cell in &grid {
-        if *cell > 1 {
-            num_intersections += 1;
+    #[test]
+    fn crooked_lines() {
+        match Day5::parse("0,9 -> 5,9\n0,0 -> 2,1\n") {
+            Err(AocError::Parse { line, .. }) => assert_eq!(2, line),
+            _ => panic!("Expected a crooked line to be rejected"),
         }
     }
-
-    println!("Number of intersections {}", num_intersections);
-}
\ No newline at end of file
+}
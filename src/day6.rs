@@ -1,12 +1,9 @@
 use super::common;
-use std::path::Path;
-
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day6_input.txt");
-    let bytes = common::read_input_bytes(input_path.as_path());
-
-    let initial_values = common::read_list_of_csv_u32s(&bytes);
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 
+/// Returns the number of fish after simulating num_days.
+fn simulate(initial_values: &[u32], num_days: u32) -> u64 {
     // Each slot represents a day remaining until duplication.
     // The slot stores how many fish there are with that lifetime remaining.
     let total_generations = 9;
@@ -14,26 +11,54 @@ pub fn run(root_dir: &Path) {
     let last_generation = 6;
     let new_generation = 8;
 
-    let mut each_generation: Vec<u64> = Vec::new();
-    each_generation.resize(total_generations, 0);
+    let mut each_generation: Vec<u64> = vec![0; total_generations];
 
-    for lifetime in &initial_values {
+    for lifetime in initial_values {
         each_generation[*lifetime as usize] += 1;
     }
 
-    let num_days = 256;
     for _ in 0..num_days {
-        
         let count_at_0 = each_generation[first_generation];
-        each_generation[first_generation] = 0;
-
-        for i in 1..total_generations {
-            each_generation[i - 1] = each_generation[i];
-        }
+        each_generation.rotate_left(1);
 
         each_generation[new_generation] = count_at_0;
         each_generation[last_generation] += count_at_0;
     }
 
-    println!("Count at {} days: {}", num_days, each_generation.iter().sum::<u64>());
-}
\ No newline at end of file
+    each_generation.iter().sum::<u64>()
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = Vec<u32>;
+
+    fn parse(input: &str) -> Result<Vec<u32>> {
+        let values = common::read_list_of_csv::<u32>(input.as_bytes())?;
+        if values.iter().any(|lifetime| *lifetime > 8) {
+            return Err(AocError::parse(1, 1, "Expected lifetimes from 0 to 8"));
+        }
+        Ok(values)
+    }
+
+    fn part1(initial_values: &Vec<u32>) -> Result<Answer> {
+        simulate(initial_values, 80).try_into()
+    }
+
+    fn part2(initial_values: &Vec<u32>) -> Result<Answer> {
+        simulate(initial_values, 256).try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
+
+    const EXAMPLE: &str = "3,4,3,1,2\n";
+
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(5934), Answer::Int(26984457539)), solve_example::<Day6>(EXAMPLE));
+    }
+}
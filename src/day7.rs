@@ -1,72 +1,77 @@
 use super::common;
-use std::path::Path;
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day7_input.txt");
-    let bytes = common::read_input_bytes(input_path.as_path());
+/// Moving n steps costs 1 + 2 + ... + n. Returns None if the total doesn't fit in a u64.
+fn get_total_cost(positions: &[u32], midpoint: u32) -> Option<u64> {
+    positions.iter().try_fold(0u64, |total_cost, pos| {
+        // A u32 distance can't overflow this in a u64.
+        let diff = pos.abs_diff(midpoint) as u64;
+        total_cost.checked_add(diff * (diff + 1) / 2)
+    })
+}
 
-    let mut positions = common::read_list_of_csv_u32s(&bytes);
-    positions.sort();
+pub struct Day7;
 
-    {
-        let median_idx = positions.len() / 2;
-        let median = positions[median_idx];
-    
-        let mut total_fuel = 0;
-        for pos in &positions {
-            let diff = (*pos as i32) - (median as i32);
-            total_fuel += diff.abs() as u32;
-        }
+impl Solution for Day7 {
+    type Input = Vec<u32>;
 
-        println!("Total fuel non-weighted {}", total_fuel);
-    }
-
-    fn get_total_cost(positions: &Vec<u32>, midpoint: u32) -> u32 {
-        let mut total_cost = 0;
-        for pos in positions {
-            let diff = ((*pos as f32) - (midpoint as f32)).abs();
-            let cost_sum = diff * ((1.0 + diff) / 2.0);
-            total_cost += cost_sum as u32;
+    fn parse(input: &str) -> Result<Vec<u32>> {
+        let mut positions = common::read_list_of_csv::<u32>(input.as_bytes())?;
+        if positions.is_empty() {
+            return Err(AocError::parse(1, 1, "Expected at least one position"));
         }
 
-        total_cost
+        positions.sort();
+        Ok(positions)
     }
 
-    {
-        let (min_pos, max_pos) = {
-            let mut min = u32::MAX;
-            let mut max = u32::MIN;
-            for pos in &positions {
-                min = u32::min(min, *pos);
-                max = u32::max(max, *pos);
-            }
-            (min, max)
-        };
+    fn part1(positions: &Vec<u32>) -> Result<Answer> {
+        let median_idx = positions.len() / 2;
+        let median = positions[median_idx];
+
+        let total_fuel = positions.iter()
+            .try_fold(0u64, |total_fuel, pos| total_fuel.checked_add(pos.abs_diff(median) as u64))
+            .ok_or_else(|| AocError::no_solution("The fuel needed doesn't fit in 64 bits"))?;
 
-        let mut pos_at_min_cost = 0;
-        let mut min_total_cost = u32::MAX;
+        total_fuel.try_into()
+    }
 
-        for i in min_pos..=max_pos {
-            let total_cost_at_i = get_total_cost(&positions, i);
-            if total_cost_at_i < min_total_cost {
-                min_total_cost = total_cost_at_i;
-                pos_at_min_cost = i;
-            }            
-        }
+    fn part2(positions: &Vec<u32>) -> Result<Answer> {
+        // The cheapest spot is within half a step of the mean, so only the integers around it
+        // are worth checking. Positions are sorted, so the first and last are the extremes.
+        let sum = positions.iter().map(|pos| *pos as u128).sum::<u128>();
+        let mean = (sum / positions.len() as u128) as u32;
+        let (min_pos, max_pos) = (positions[0], positions[positions.len() - 1]);
+        let candidates = mean.saturating_sub(1).max(min_pos)..=mean.saturating_add(2).min(max_pos);
 
-        println!("Total weighted cost (bruteforce) {} ", min_total_cost);
+        // If the cheapest spot overflows, so does every other one.
+        candidates.filter_map(|i| get_total_cost(positions, i))
+            .min()
+            .ok_or_else(|| AocError::no_solution("The fuel needed doesn't fit in 64 bits"))?
+            .try_into()
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
 
-    {
-        let mean = {
-            let total: u32 = positions.iter().sum();
-            let mean_f = total as f32 / positions.len() as f32;
+    const EXAMPLE: &str = "16,1,2,0,4,2,7,1,2,14\n";
 
-            f32::min(mean_f.floor(), mean_f.ceil()) as u32
-        };
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(37), Answer::Int(168)), solve_example::<Day7>(EXAMPLE));
+    }
 
-        let total_fuel = get_total_cost(&positions, mean);
+    #[test]
+    fn far_apart() {
+        let positions = Day7::parse("2000000000,0,4000000000\n").unwrap();
+        assert_eq!(Answer::Int(4_000_000_000), Day7::part1(&positions).unwrap());
+        assert_eq!(Answer::Int(4_000_000_002_000_000_000), Day7::part2(&positions).unwrap());
 
-        println!("Total weighted cost (mean) {} ", total_fuel);
+        let positions = Day7::parse("0,0,0,4294967295,4294967295,4294967295\n").unwrap();
+        assert!(matches!(Day7::part2(&positions), Err(AocError::NoSolution { .. })));
     }
-}
\ No newline at end of file
+}
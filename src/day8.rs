@@ -1,177 +1,134 @@
-use super::common;
-use std::path::Path;
+use super::common::bitset::BitSet;
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 
-#[derive(Default, Copy, Clone)]
-struct BitMask {
-    mask: u8
-}
-
-impl BitMask {
-    pub fn set_bit(&mut self, i: usize) {
-        assert!(i < 7); // we only use 7 bits, one for each segment.
-        self.mask |= 1 << i; 
-    }
-
-    pub fn and(&self, other: BitMask) -> BitMask {
-        BitMask { mask: self.mask & other.mask }
-    }
-
-    pub fn not(&self, other: BitMask) -> BitMask {
-        BitMask { mask: self.mask & (!other.mask) }
-    }
-
-    pub fn popcnt(&self) -> u32 {
-        self.mask.count_ones()
-    }
-
-    pub fn contains_all(&self, other: BitMask) -> bool {
-        (self.mask & other.mask) == other.mask
-    }
-}
+/// The lit segments of a display, one bit for each of 'a' to 'g'.
+type Segments = BitSet;
 
 fn bit_idx(char: u8) -> usize {
-    ((char) - ('a' as u8)) as usize
+    (char - b'a') as usize
 }
 
 #[derive(Default)]
-struct Sample {
-    patterns: [BitMask; 10],
-    digits: [BitMask; 4],
+pub struct Sample {
+    patterns: [Segments; 10],
+    digits: [Segments; 4],
 }
 
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day8_input.txt");
-    let bytes = common::read_input_bytes(input_path.as_path());
+/// Parses space separated patterns of segments 'a' to 'g' into masks.
+/// The line is a part of the input line starting at start_col, for error reporting.
+fn parse_patterns(masks: &mut [Segments], line: &str, line_idx: usize, start_col: usize) -> Result<()> {
+    let mut words = Vec::with_capacity(masks.len());
+    let mut col = start_col;
+    for word in line.split(' ') {
+        if !word.trim().is_empty() {
+            words.push((col, word.trim()));
+        }
+        col += word.len() + 1;
+    }
 
-    let mut samples = Vec::new();
-    {
-        let mut cursor = 0;
-        loop {
-            let mut sample = Sample::default();
-            for i in 0..10 {
-                let mut mask = BitMask::default();
-                while (bytes[cursor] as char).is_ascii_alphabetic() {
-                    mask.set_bit(bit_idx(bytes[cursor]));
-                    cursor += 1;
-                }
-                
-                cursor += 1;
-                sample.patterns[i] = mask;
-            }
+    if words.len() != masks.len() {
+        return Err(AocError::parse(line_idx + 1, start_col + 1,
+            format!("Expected {} patterns, found {}", masks.len(), words.len())));
+    }
 
-            while !(bytes[cursor] as char).is_ascii_alphabetic() {
-                cursor += 1;
+    for (mask, (word_col, word)) in masks.iter_mut().zip(words) {
+        for (i, c) in word.bytes().enumerate() {
+            if !(b'a'..=b'g').contains(&c) {
+                return Err(AocError::parse(line_idx + 1, word_col + i + 1,
+                    format!("Unexpected segment '{}'", c as char)));
             }
+            mask.insert(bit_idx(c));
+        }
+    }
 
-            for i in 0..4 {
-                let mut mask = BitMask::default();                
-                while (cursor < bytes.len()) && (bytes[cursor] as char).is_ascii_alphabetic() {
-                    mask.set_bit(bit_idx(bytes[cursor]));
-                    cursor += 1;
-                }
-                
-                cursor += 1;
-                sample.digits[i] = mask;
-            }            
-
-            samples.push(sample);
+    Ok(())
+}
 
-            if cursor >= bytes.len() {
-                break;
-            }
+fn parse_samples(input: &str) -> Result<Vec<Sample>> {
+    let mut samples = Vec::new();
 
-            while !(bytes[cursor] as char).is_ascii_alphabetic() {
-                cursor += 1;
-            }
+    for (line_idx, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
         }
-    }
 
-    let mut num_unique_digits = 0;
-    for sample in &samples {
-        for digit in &sample.digits {
-            match digit.popcnt() {
-                2 | 3 | 4 | 7  => num_unique_digits += 1,
-                _ => (),
-            }
-        }
-    }
+        let (patterns, digits) = line.split_once('|').ok_or_else(|| {
+            AocError::parse(line_idx + 1, line.len() + 1, "Expected '|' between patterns and digits")
+        })?;
 
-    println!("Num output digits with unqiue counts: {}", num_unique_digits);
+        let mut sample = Sample::default();
+        parse_patterns(&mut sample.patterns, patterns, line_idx, 0)?;
+        parse_patterns(&mut sample.digits, digits, line_idx, patterns.len() + 1)?;
+        samples.push(sample);
+    }
 
     // Sort by set bits ascending, this way we can reliably
     // grab the patterns with unique num bits by index:
     // Bits: [2, 3, 4, 5, 5, 5, 6, 6, 6, 7]
     // Num:  [1, 7, 4, ?, ?, ?, ?, ?, ?, 8]
     for sample in &mut samples {
-        sample.patterns.sort_by(|lhs, rhs| {
-            let pcnt_l = lhs.popcnt();
-            let pcnt_r = rhs.popcnt();
-
-            pcnt_l.partial_cmp(&pcnt_r).unwrap()
-        });
+        sample.patterns.sort_by_key(Segments::len);
     }
 
-    #[allow(unused)]
-    fn print_bin(mask: BitMask) {
-        println!("  0gfedcba");
-        println!("{:#010b}", mask.mask);
-    }
+    Ok(samples)
+}
 
-    fn bit_i(mask: BitMask) -> u8 {
-        assert!(mask.popcnt() == 1);
-        mask.mask.trailing_zeros() as u8
+/// Deduces the wire mapping of each sample and sums up their output values.
+fn sum_output_values(samples: &[Sample]) -> Result<u32> {
+    fn bit_i(mask: Segments) -> Result<usize> {
+        match mask.select(0) {
+            Some(bit) if mask.len() == 1 => Ok(bit),
+            _ => Err(AocError::no_solution("Patterns don't map to a single wire")),
+        }
     }
 
+    let all_segments: Segments = (0..7).collect();
+
     let mut mapping_tables = Vec::new();
 
-    for sample in &samples {
-        let mut mapping: [u8;7] = Default::default();
+    for sample in samples {
+        let mut mapping: [usize; 7] = Default::default();
 
         let one = sample.patterns[0];
         let seven = sample.patterns[1];
         let four = sample.patterns[2];
 
-        let c_and_f = one.and(seven);
-        let b_and_d = four.not(one);
-        let a = seven.not(c_and_f);
+        let c_and_f = one & seven;
+        let b_and_d = four & !one;
+        let a = seven & !c_and_f;
 
-        mapping[0] = bit_i(a);
+        mapping[0] = bit_i(a)?;
 
         let three = *sample.patterns.iter().find(|p| {
-            p.popcnt() == 5 &&
-            p.contains_all(a) &&
-            p.contains_all(c_and_f)
-        }).unwrap();
+            p.len() == 5 &&
+            p.is_superset(&a) &&
+            p.is_superset(&c_and_f)
+        }).ok_or_else(|| AocError::no_solution("No pattern for 3"))?;
 
-        let g = three.not(a).not(c_and_f).not(b_and_d);
-        mapping[6] = bit_i(g);
+        let g = three & !(a | c_and_f | b_and_d);
+        mapping[6] = bit_i(g)?;
 
-        let d = b_and_d.and(three).and(four);
-        let b = b_and_d.not(d);
+        let d = b_and_d & three & four;
+        let b = b_and_d & !d;
 
-        mapping[1] = bit_i(b);
-        mapping[3] = bit_i(d);
+        mapping[1] = bit_i(b)?;
+        mapping[3] = bit_i(d)?;
 
         let five = *sample.patterns.iter().find(|p| {
-            p.popcnt() == 5 &&
-            p.contains_all(a) &&
-            p.contains_all(g) &&
-            p.contains_all(d) &&
-            p.contains_all(b)
-        }).unwrap();
+            p.len() == 5 &&
+            p.is_superset(&(a | g | d | b))
+        }).ok_or_else(|| AocError::no_solution("No pattern for 5"))?;
 
-        let f = c_and_f.and(five);
-        let c = c_and_f.not(f);
+        let f = c_and_f & five;
+        let c = c_and_f & !f;
 
-        mapping[2] = bit_i(c);
-        mapping[5] = bit_i(f);
+        mapping[2] = bit_i(c)?;
+        mapping[5] = bit_i(f)?;
 
-        let e = {
-            let all_bits = BitMask { mask: 127 };
-            all_bits.not(a).not(b).not(c).not(d).not(f).not(g)
-        };
+        let e = all_segments & !(a | b | c | d | f | g);
 
-        mapping[4] = bit_i(e);
+        mapping[4] = bit_i(e)?;
         mapping_tables.push(mapping);
     }
         
@@ -185,33 +142,80 @@ pub fn run(root_dir: &Path) {
 
     let mut total_sum = 0;
 
-    for i in 0..samples.len() {
-        let map = &mapping_tables[i];
-        
-        fn bit(i: u8) -> u8 { 1 << i }
-        fn not_bit(i: u8) -> u8 { !(1 << i) }
-
-        let mut pattern_to_value: [u8; 10] = Default::default();
-        pattern_to_value[0] = 127 & not_bit(map[3]);
-        pattern_to_value[1] = bit(map[2]) | bit(map[5]);
-        pattern_to_value[2] = 127 & not_bit(map[1]) & not_bit(map[5]);
-        pattern_to_value[3] = 127 & not_bit(map[1]) & not_bit(map[4]);
-        pattern_to_value[4] = bit(map[1]) | bit(map[2]) | bit(map[3]) | bit(map[5]);
-        pattern_to_value[5] = 127 & not_bit(map[2]) & not_bit(map[4]);
-        pattern_to_value[6] = 127 & not_bit(map[2]);
-        pattern_to_value[7] = bit(map[0]) | bit(map[2]) | bit(map[5]);
-        pattern_to_value[8] = 127;
-        pattern_to_value[9] = 127 & not_bit(map[4]);
-
-        for dig_idx in 0..4 {
-            let digit = &samples[i].digits[dig_idx];
-
+    for (sample, map) in samples.iter().zip(&mapping_tables) {        
+        let wire = |segment: usize| Segments::from_iter([map[segment]]);
+
+        let mut pattern_to_value: [Segments; 10] = Default::default();
+        pattern_to_value[0] = all_segments & !wire(3);
+        pattern_to_value[1] = wire(2) | wire(5);
+        pattern_to_value[2] = all_segments & !wire(1) & !wire(5);
+        pattern_to_value[3] = all_segments & !wire(1) & !wire(4);
+        pattern_to_value[4] = wire(1) | wire(2) | wire(3) | wire(5);
+        pattern_to_value[5] = all_segments & !wire(2) & !wire(4);
+        pattern_to_value[6] = all_segments & !wire(2);
+        pattern_to_value[7] = wire(0) | wire(2) | wire(5);
+        pattern_to_value[8] = all_segments;
+        pattern_to_value[9] = all_segments & !wire(4);
+
+        for (dig_idx, digit) in sample.digits.iter().enumerate() {
             let value = pattern_to_value.iter().
-                position(|p| *p == digit.mask).unwrap();
+                position(|p| p == digit)
+                .ok_or_else(|| AocError::no_solution("Output doesn't match any digit"))?;
 
             total_sum += value as u32 * 10u32.pow(3 - dig_idx as u32);
         }
     }
 
-    println!("Total digit sum: {}", total_sum);
-}
\ No newline at end of file
+    Ok(total_sum)
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input = Vec<Sample>;
+
+    fn parse(input: &str) -> Result<Vec<Sample>> {
+        parse_samples(input)
+    }
+
+    fn part1(samples: &Vec<Sample>) -> Result<Answer> {
+        let mut num_unique_digits = 0;
+        for sample in samples {
+            for digit in &sample.digits {
+                if let 2 | 3 | 4 | 7 = digit.len() {
+                    num_unique_digits += 1;
+                }
+            }
+        }
+
+        Ok(num_unique_digits.into())
+    }
+
+    fn part2(samples: &Vec<Sample>) -> Result<Answer> {
+        Ok(sum_output_values(samples)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
+
+    const EXAMPLE: &str = "\
+be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
+gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
+";
+
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(26), Answer::Int(61229)), solve_example::<Day8>(EXAMPLE));
+    }
+}
@@ -1,101 +1,86 @@
-use super::common;
-use std::path::Path;
+use super::common::Grid;
+use super::error::{AocError, Result};
+use super::solution::{Answer, Solution};
 
-pub fn run(root_dir: &Path) {
-    let input_path = root_dir.join("day9_input.txt");
-    let bytes = common::read_input_bytes(input_path.as_path());
+/// Positions where every neighbor is higher.
+fn low_points(map: &Grid<u8>) -> Vec<(usize, usize)> {
+    map.enumerate()
+        .filter(|(pos, height)| map.neighbors4(*pos).all(|n| map[n] > **height))
+        .map(|(pos, _)| pos)
+        .collect()
+}
 
-    let (heightmap, map_width, map_height) = common::parse_grid(&bytes);
+pub struct Day9;
 
-    let val_at = |x: i32, y: i32| -> u8 {
-        let idx = x as usize + (y as usize * map_width);
-        heightmap[idx]
-    };
+impl Solution for Day9 {
+    type Input = Grid<u8>;
 
-    let mut low_points = Vec::new();
-    low_points.reserve(100);
-
-    let tap_cmp = |x: i32, y: i32, cmp: u8, result_on_fail: bool| -> bool {
-        if x < 0 || x >= map_width as i32 {
-            return result_on_fail;
-        } 
-        if y < 0 || y >= map_height as i32 {
-            return result_on_fail;
-        }
-
-        cmp < val_at(x, y)
-    };
-
-    let tap_cmp_lt = |x: i32, y: i32, cmp: u8| -> bool {
-        tap_cmp(x, y, cmp, true)
-    };
-
-    let tap_cmp_gt = |x: i32, y: i32, cmp: u8| -> bool {
-        tap_cmp(x, y, cmp, false)
-    };
+    fn parse(input: &str) -> Result<Grid<u8>> {
+        Grid::parse_digits(input)
+    }
 
-    for y in 0..(map_height as i32) {
-        for x in 0..(map_width as i32) {
-            let center = val_at(x, y);
-            let mut is_low = true;
-            is_low &= tap_cmp_lt(x - 1, y, center);
-            is_low &= tap_cmp_lt(x + 1, y, center);
-            is_low &= tap_cmp_lt(x, y - 1, center);
-            is_low &= tap_cmp_lt(x, y + 1, center);
+    fn part1(map: &Grid<u8>) -> Result<Answer> {
+        let low_points = low_points(map);
+        let low_points_sum = low_points.iter()
+            .map(|pos| map[*pos] as u32).sum::<u32>();
 
-            if is_low {
-                low_points.push((x, y));
-            }
-        }
+        Ok((low_points_sum + low_points.len() as u32).into())
     }
 
-    let low_points_sum = low_points.iter()
-        .map(|(x, y)| val_at(*x, *y) as u32).sum::<u32>();
-
-    println!("Total risk score {}", low_points_sum + low_points.len() as u32);
+    fn part2(map: &Grid<u8>) -> Result<Answer> {
+        let low_points = low_points(map);
+        let mut largest_basins = Vec::with_capacity(low_points.len());
+        let mut closed = map.map(|_| false);
 
-    let mut largest_basins = Vec::new();
-    largest_basins.reserve(low_points.len());
+        for low_point in &low_points {
+            let mut size = 0;
+            let mut queue = vec![*low_point];
 
-    for (low_x, low_y) in &low_points {
-        let mut size = 0;
-        let mut queue: Vec<(i32, i32)> = Vec::new();
-        let mut closed: Vec<(i32, i32)> = Vec::new();
-        queue.push((*low_x, *low_y));
+            while let Some(pos) = queue.pop() {
+                if closed[pos] {
+                    continue;
+                }
 
-        while queue.len() > 0 {
-            let (x, y) = queue.pop().unwrap();
+                size += 1;
+                closed[pos] = true;
 
-            if closed.iter().any(|c| (c.0 == x) && (c.1 == y)) {
-                continue;
+                // Flow uphill until we hit the 9s around the basin.
+                let point = map[pos];
+                queue.extend(map.neighbors4(pos).filter(|n| map[*n] > point && map[*n] != 9));
             }
-            
-            size += 1;
-            closed.push((x, y));
-
-            let point = val_at(x, y);
-
-            let mut floodfill_add = |p_x: i32, p_y: i32, neighbor: u8| {
-                if tap_cmp_gt(p_x, p_y, neighbor) && (val_at(p_x, p_y) != 9) {
-                    queue.push((p_x, p_y));
-                }
-            };
 
-            floodfill_add(x + 1, y, point);
-            floodfill_add(x - 1, y, point);
-            floodfill_add(x, y + 1, point);
-            floodfill_add(x, y - 1, point);
+            largest_basins.push(size);
         }
 
-        largest_basins.push(size);
-    }
+        if largest_basins.len() < 3 {
+            return Err(AocError::no_solution("Expected at least 3 basins"));
+        }
 
-    largest_basins.sort();
+        largest_basins.sort();
 
-    let b0 = largest_basins.pop().unwrap();
-    let b1 = largest_basins.pop().unwrap();
-    let b2 = largest_basins.pop().unwrap();
+        let b0 = largest_basins.pop().unwrap();
+        let b1 = largest_basins.pop().unwrap();
+        let b2 = largest_basins.pop().unwrap();
 
-    println!("Three largest: {}, {}, {}", b0, b1, b2);
-    println!("Total basin size: {}", b0 * b1 * b2);
-}
\ No newline at end of file
+        Ok((b0 * b1 * b2).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solve_example;
+
+    const EXAMPLE: &str = "\
+2199943210
+3987894921
+9856789892
+8767896789
+9899965678
+";
+
+    #[test]
+    fn example() {
+        assert_eq!((Answer::Int(15), Answer::Int(1134)), solve_example::<Day9>(EXAMPLE));
+    }
+}
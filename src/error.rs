@@ -0,0 +1,135 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+pub type Result<T> = std::result::Result<T, AocError>;
+
+#[derive(Debug)]
+pub enum AocError {
+    /// Reading the input failed. The path is missing when reading from a stream like stdin.
+    Io { path: Option<PathBuf>, source: io::Error },
+    /// The input doesn't look like we expected. Lines and columns start at 1, the day is
+    /// filled in by the runner since the parsers don't know which day they belong to.
+    Parse { day: Option<u32>, line: usize, column: usize, message: String },
+    /// The input parsed fine, but the solver couldn't find an answer for it.
+    NoSolution { day: Option<u32>, message: String },
+    /// The solver panicked. Caught by the runner so the other days still get to run.
+    Panic { day: Option<u32>, message: String },
+}
+
+impl AocError {
+    pub fn parse(line: usize, column: usize, message: impl Into<String>) -> AocError {
+        AocError::Parse { day: None, line, column, message: message.into() }
+    }
+
+    /// A parse error at a byte offset into the input, for the cursor based parsers.
+    pub fn parse_at(bytes: &[u8], offset: usize, message: impl Into<String>) -> AocError {
+        let offset = offset.min(bytes.len());
+        let line_start = bytes[..offset].iter().rposition(|b| *b == b'\n').map_or(0, |pos| pos + 1);
+        let line = bytes[..line_start].iter().filter(|b| **b == b'\n').count() + 1;
+        AocError::parse(line, offset - line_start + 1, message)
+    }
+
+    pub fn no_solution(message: impl Into<String>) -> AocError {
+        AocError::NoSolution { day: None, message: message.into() }
+    }
+
+    pub fn panic(message: impl Into<String>) -> AocError {
+        AocError::Panic { day: None, message: message.into() }
+    }
+
+    /// Moves a parse error produced for a single line of the input onto that line.
+    pub fn on_line(self, line: usize) -> AocError {
+        match self {
+            AocError::Parse { day, column, message, .. } => AocError::Parse { day, line, column, message },
+            other => other,
+        }
+    }
+
+    /// Moves a parse error down by some lines, for errors from parsing a later section of the input.
+    pub fn shift_lines(self, by: usize) -> AocError {
+        match self {
+            AocError::Parse { day, line, column, message } => AocError::Parse { day, line: line + by, column, message },
+            other => other,
+        }
+    }
+
+    pub fn with_day(self, day: u32) -> AocError {
+        match self {
+            AocError::Parse { line, column, message, .. } => {
+                AocError::Parse { day: Some(day), line, column, message }
+            },
+            AocError::NoSolution { message, .. } => AocError::NoSolution { day: Some(day), message },
+            AocError::Panic { message, .. } => AocError::Panic { day: Some(day), message },
+            io => io,
+        }
+    }
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AocError::Io { path: Some(path), source } => {
+                write!(f, "Failed to read {}: {}", path.display(), source)
+            },
+            AocError::Io { path: None, source } => write!(f, "Failed to read input: {}", source),
+            AocError::Parse { day, line, column, message } => {
+                if let Some(day) = day {
+                    write!(f, "Day {} ", day)?;
+                }
+                write!(f, "parse error at line {}, column {}: {}", line, column, message)
+            },
+            AocError::NoSolution { day, message } => {
+                if let Some(day) = day {
+                    write!(f, "Day {} ", day)?;
+                }
+                write!(f, "found no solution: {}", message)
+            },
+            AocError::Panic { day, message } => {
+                if let Some(day) = day {
+                    write!(f, "Day {} ", day)?;
+                }
+                write!(f, "panicked: {}", message)
+            },
+        }
+    }
+}
+
+impl std::error::Error for AocError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AocError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_at_offset() {
+        let input = b"12\n345\n6x";
+        match AocError::parse_at(input, 8, "bad digit") {
+            AocError::Parse { line, column, .. } => assert_eq!((3, 2), (line, column)),
+            _ => unreachable!(),
+        }
+        match AocError::parse_at(input, 0, "bad digit") {
+            AocError::Parse { line, column, .. } => assert_eq!((1, 1), (line, column)),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn display() {
+        let err = AocError::parse(4, 2, "expected a number").with_day(7);
+        assert_eq!("Day 7 parse error at line 4, column 2: expected a number", err.to_string());
+
+        let err = AocError::no_solution("scanners don't overlap");
+        assert_eq!("found no solution: scanners don't overlap", err.to_string());
+
+        let err = AocError::panic("attempt to divide by zero").with_day(24);
+        assert_eq!("Day 24 panicked: attempt to divide by zero", err.to_string());
+    }
+}
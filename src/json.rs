@@ -0,0 +1,140 @@
+use super::solution::Answer;
+use std::fmt;
+
+/// A JSON value, just enough to report results without pulling in a dependency.
+/// Object fields keep the order they were added in.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    /// Non-finite floats have no JSON representation and are written as null.
+    Float(f64),
+    Str(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Builds an object from (key, value) pairs.
+    pub fn object<K: Into<String>>(fields: impl IntoIterator<Item = (K, Value)>) -> Value {
+        Value::Object(fields.into_iter().map(|(key, value)| (key.into(), value)).collect())
+    }
+}
+
+fn write_str(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+/// Writes compact JSON with no whitespace between tokens.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(x) => write!(f, "{}", x),
+            Value::Float(x) if x.is_finite() => write!(f, "{}", x),
+            Value::Float(_) => f.write_str("null"),
+            Value::Str(s) => write_str(f, s),
+            Value::Array(values) => {
+                f.write_str("[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_str("]")
+            },
+            Value::Object(fields) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_str(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_str("}")
+            },
+        }
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Value {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Value {
+        Value::Str(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::Str(s)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(x: u32) -> Value {
+        Value::Int(x.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Value {
+        Value::Float(x)
+    }
+}
+
+/// Numbers stay numbers, grids become a string with one line per row, and a missing answer is null.
+impl From<&Answer> for Value {
+    fn from(answer: &Answer) -> Value {
+        match answer {
+            Answer::Int(x) => Value::Int(*x),
+            Answer::Str(s) => Value::Str(s.clone()),
+            Answer::Grid(grid) => Value::Str(grid.trim_end().to_string()),
+            Answer::None => Value::Null,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize() {
+        let value = Value::object([
+            ("day", Value::from(13u32)),
+            ("answer", Value::from(&Answer::Grid("* *\n *\n".to_string()))),
+            ("time", Value::from(0.25)),
+            ("error", Value::from(None::<String>)),
+            ("list", Value::Array(vec![Value::Bool(true), Value::Int(-3), Value::Float(f64::NAN)])),
+        ]);
+        assert_eq!(r#"{"day":13,"answer":"* *\n *","time":0.25,"error":null,"list":[true,-3,null]}"#,
+                   value.to_string());
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(r#""a\"b\\c\td\u0001é""#, Value::from("a\"b\\c\td\u{1}é").to_string());
+        assert_eq!("{}", Value::object(Vec::<(String, Value)>::new()).to_string());
+    }
+}
@@ -0,0 +1,115 @@
+//! Solutions for Advent of Code 2021, along with the helpers they share.
+//!
+//! Every day exposes a `DayN` type implementing [`Solution`]. [`solve_str`], [`solve_reader`] and
+//! [`solve`] run a day by number on an input string, a stream or a file.
+//! The `aoc21` binary is a thin command line front end on top of this crate.
+
+pub mod answers;
+pub mod common;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod error;
+pub mod json;
+pub mod pool;
+pub mod solution;
+pub mod timing;
+
+pub use error::{AocError, Result};
+pub use solution::{Answer, DayAnswers, Parts, PhaseTimes, Solution};
+
+use std::io::BufRead;
+use std::path::Path;
+
+pub const NUM_DAYS: u32 = 25;
+
+/// Entry points for each day, indexed by day - 1.
+pub const DAYS: [fn(&str, Parts) -> Result<DayAnswers>; NUM_DAYS as usize] = [
+    solution::run_str::<day1::Day1>,
+    solution::run_str::<day2::Day2>,
+    solution::run_str::<day3::Day3>,
+    solution::run_str::<day4::Day4>,
+    solution::run_str::<day5::Day5>,
+    solution::run_str::<day6::Day6>,
+    solution::run_str::<day7::Day7>,
+    solution::run_str::<day8::Day8>,
+    solution::run_str::<day9::Day9>,
+    solution::run_str::<day10::Day10>,
+    solution::run_str::<day11::Day11>,
+    solution::run_str::<day12::Day12>,
+    solution::run_str::<day13::Day13>,
+    solution::run_str::<day14::Day14>,
+    solution::run_str::<day15::Day15>,
+    solution::run_str::<day16::Day16>,
+    solution::run_str::<day17::Day17>,
+    solution::run_str::<day18::Day18>,
+    solution::run_str::<day19::Day19>,
+    solution::run_str::<day20::Day20>,
+    solution::run_str::<day21::Day21>,
+    solution::run_str::<day22::Day22>,
+    solution::run_str::<day23::Day23>,
+    solution::run_str::<day24::Day24>,
+    solution::run_str::<day25::Day25>,
+];
+
+/// Solves the requested parts of the given day on the input.
+/// Errors are tagged with the day. Panics if the day isn't in 1..=NUM_DAYS.
+pub fn solve_str(day: u32, input: &str, parts: Parts) -> Result<DayAnswers> {
+    assert!((1..=NUM_DAYS).contains(&day), "Unknown day {}", day);
+    DAYS[day as usize - 1](input, parts).map_err(|err| err.with_day(day))
+}
+
+/// Reads the whole input from a stream, like stdin, and solves it. See [`solve_str`].
+pub fn solve_reader(day: u32, reader: impl BufRead, parts: Parts) -> Result<DayAnswers> {
+    solve_str(day, &common::read_input_string(reader)?, parts)
+}
+
+/// Reads the input file and solves it. See [`solve_str`].
+pub fn solve(day: u32, input_path: &Path, parts: Parts) -> Result<DayAnswers> {
+    let input_bytes = common::read_input_bytes(input_path)?;
+    solve_str(day, &String::from_utf8_lossy(&input_bytes), parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solve_from_memory() {
+        let answers = solve_reader(7, "16,1,2,0,4,2,7,1,2,14\n".as_bytes(), Parts::BOTH).unwrap();
+        assert_eq!(Some(Answer::Int(37)), answers.part1);
+        assert_eq!(Some(Answer::Int(168)), answers.part2);
+
+        let answers = solve_str(1, "199\n200\n", Parts { one: true, two: false }).unwrap();
+        assert_eq!(Some(Answer::Int(1)), answers.part1);
+        assert_eq!(None, answers.part2);
+    }
+
+    #[test]
+    fn errors_name_the_day() {
+        let err = solve_str(7, "16,x", Parts::BOTH).unwrap_err();
+        assert_eq!("Day 7 parse error at line 1, column 4: Expected a number", err.to_string());
+    }
+}
@@ -1,91 +1,248 @@
-mod common;
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
+mod cli;
 
+use aoc21::answers::{Check, KnownAnswers};
+use aoc21::{json, pool, timing, Answer, AocError, Parts};
+use cli::Format;
+use std::fmt::Write;
 use std::path::Path;
 
+fn load_known_answers(path: &Path) -> Result<KnownAnswers, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+    KnownAnswers::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+/// Writes a part's answer, along with how it compares to the recorded one when verifying.
+fn write_text_answer(out: &mut String, part: u32, answer: &Answer, check: Option<&Check>) {
+    let status = match check {
+        None => String::new(),
+        Some(Check::Match) => " [ok]".to_string(),
+        Some(Check::Mismatch { expected }) => format!(" [MISMATCH, expected {}]", expected),
+        Some(Check::Missing) => " [no recorded answer]".to_string(),
+    };
+
+    writeln!(out, "Part {}: {}{}", part, answer, status).unwrap();
+}
+
+/// Writes one JSON line for each selected part. A failed day reports its error on every part.
+fn write_json_parts(out: &mut String, day: u32, parts: Parts, report: &DayReport) {
+    let selected = [(1, parts.one), (2, parts.two)];
+    for part in selected.iter().filter(|(_, selected)| *selected).map(|(part, _)| *part) {
+        let answer = report.answers.iter().find(|(_, p, _)| *p == part).map(|(_, _, answer)| answer);
+        let duration = report.timing.as_ref()
+            .and_then(|timing| if part == 1 { timing.times.part1 } else { timing.times.part2 });
+
+        let line = json::Value::object([
+            ("day", json::Value::from(day)),
+            ("part", json::Value::from(part)),
+            ("answer", json::Value::from(answer)),
+            ("duration_ms", json::Value::from(duration.map(timing::millis))),
+            ("error", json::Value::from(report.error.as_ref().map(|err| err.to_string()))),
+        ]);
+        writeln!(out, "{}", line).unwrap();
+    }
+}
+
+/// Everything a day produced. The output is buffered so days can run on other threads.
+struct DayReport {
+    output: String,
+    error: Option<AocError>,
+    num_mismatches: usize,
+    answers: Vec<(u32, u32, Answer)>,
+    timing: Option<timing::DayTiming>,
+}
+
+/// Runs a day on its input file, or on `stdin_input` when reading from stdin.
+fn run_day(day: u32, options: &cli::Options, stdin_input: Option<&str>, verify_against: Option<&KnownAnswers>) -> DayReport {
+    let mut report = DayReport {
+        output: String::new(),
+        error: None,
+        num_mismatches: 0,
+        answers: Vec::new(),
+        timing: None,
+    };
+
+    let solve = || match stdin_input {
+        Some(input) => aoc21::solve_str(day, input, options.parts),
+        None => aoc21::solve(day, &options.input_path(day), options.parts),
+    };
+
+    let mut runs = Vec::new();
+    match solve() {
+        Ok(answers) => {
+            runs.push(answers.times);
+            for (part, answer) in [(1, answers.part1), (2, answers.part2)] {
+                report.answers.extend(answer.map(|answer| (day, part, answer)));
+            }
+        },
+        Err(err) => report.error = Some(err),
+    }
+
+    if options.timing_enabled() && report.error.is_none() {
+        for _ in 1..options.repeat {
+            match solve() {
+                Ok(answers) => runs.push(answers.times),
+                Err(err) => {
+                    report.error = Some(err);
+                    break;
+                }
+            }
+        }
+    }
+
+    if !runs.is_empty() {
+        report.timing = Some(timing::DayTiming::from_runs(day, &runs));
+    }
+
+    let checks = report.answers.iter()
+        .map(|(day, part, answer)| verify_against.map(|known| known.check(*day, *part, answer)))
+        .collect::<Vec<_>>();
+    report.num_mismatches = checks.iter().filter(|check| matches!(check, Some(Check::Mismatch { .. }))).count();
+
+    let mut output = String::new();
+    match options.format {
+        Format::Text => {
+            writeln!(output, "Running day {}", day).unwrap();
+            for ((_, part, answer), check) in report.answers.iter().zip(&checks) {
+                write_text_answer(&mut output, *part, answer, check.as_ref());
+            }
+        },
+        Format::Json => write_json_parts(&mut output, day, options.parts, &report),
+    }
+    report.output = output;
+
+    report
+}
+
+/// What's left to report of a day whose solver panicked. Only that day is lost.
+fn panicked_report(day: u32, message: String, options: &cli::Options) -> DayReport {
+    let mut report = DayReport {
+        output: String::new(),
+        error: Some(AocError::panic(message).with_day(day)),
+        num_mismatches: 0,
+        answers: Vec::new(),
+        timing: None,
+    };
+
+    let mut output = String::new();
+    match options.format {
+        Format::Text => writeln!(output, "Running day {}", day).unwrap(),
+        Format::Json => write_json_parts(&mut output, day, options.parts, &report),
+    }
+    report.output = output;
+
+    report
+}
+
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let options = match cli::parse_args(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let answers_path = options.answers_path();
+    let mut known_answers = None;
+    if options.verify || options.bless {
+        let path = answers_path.as_deref().unwrap();
+        // Blessing may start a fresh file, verifying needs an existing one.
+        known_answers = match load_known_answers(path) {
+            Ok(known) => Some(known),
+            Err(_) if options.bless && !path.exists() => Some(KnownAnswers::default()),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(2);
+            }
+        };
+    }
+
+    // Stdin can only be read once, so grab all of it before running the day.
+    let stdin_input = match options.stdin {
+        true => match aoc21::common::read_input_string(std::io::stdin().lock()) {
+            Ok(input) => Some(input),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        },
+        false => None,
+    };
+
+    let verify_against = if options.verify { known_answers.as_ref() } else { None };
+    let mut num_mismatches = 0;
+    let mut num_failed = 0;
+    let mut blessed = Vec::new();
+    let mut timings = Vec::new();
 
-    if args.len() < 2 {
-        print!("Not enough args provided!
-                Usage: -- <path_to_input_files>");
-        return;
+    let num_threads = match options.jobs {
+        0 => pool::default_num_threads(),
+        jobs => jobs,
+    };
+
+    pool::run_ordered(&options.days, num_threads, |&day| {
+        run_day(day, &options, stdin_input.as_deref(), verify_against)
+    }, |&day, report| {
+        let report = report.unwrap_or_else(|message| panicked_report(day, message, &options));
+        print!("{}", report.output);
+        if let Some(err) = &report.error {
+            // JSON output already carries the error.
+            if options.format == Format::Text {
+                eprintln!("{}", err);
+            }
+            num_failed += 1;
+        }
+        num_mismatches += report.num_mismatches;
+        if options.bless {
+            blessed.extend(report.answers);
+        }
+        timings.extend(report.timing);
+    });
+
+    if options.timing_enabled() && options.format == Format::Text {
+        println!();
+        timing::print_table(&timings);
+    }
+
+    if let Some(csv_path) = &options.csv {
+        let written = std::fs::File::create(csv_path)
+            .and_then(|file| timing::write_csv(std::io::BufWriter::new(file), &timings));
+
+        if let Err(err) = written {
+            eprintln!("Failed to write timings to {}: {}", csv_path.display(), err);
+            std::process::exit(1);
+        }
     }
 
-    let root_dir = Path::new(&args[1]);
-
-    println!("Running day 1");
-    day1::run(root_dir);
-    println!("Running day 2");
-    day2::run(root_dir);
-    println!("Running day 3");
-    day3::run(root_dir);
-    println!("Running day 4");
-    day4::run(root_dir);
-    println!("Running day 5");
-    day5::run(root_dir);
-    println!("Running day 6");
-    day6::run(root_dir);
-    println!("Running day 7");
-    day7::run(root_dir);
-    println!("Running day 8");
-    day8::run(root_dir);
-    println!("Running day 9");
-    day9::run(root_dir);
-    println!("Running day 10");
-    day10::run(root_dir);
-    println!("Running day 11");
-    day11::run(root_dir);
-    println!("Running day 12");
-    day12::run(root_dir);
-    println!("Running day 13");
-    day13::run(root_dir);
-    println!("Running day 14");
-    day14::run(root_dir);
-    println!("Running day 15");
-    day15::run(root_dir);
-    println!("Running day 16");
-    day16::run(root_dir);
-    println!("Running day 17");
-    day17::run(root_dir);
-    println!("Running day 18");
-    day18::run(root_dir);
-    println!("Running day 19");
-    day19::run(root_dir);
-    println!("Running day 20");
-    day20::run(root_dir);
-    println!("Running day 21");
-    day21::run(root_dir);
-    println!("Running day 22");
-    day22::run(root_dir);
-    println!("Running day 23");
-    day23::run(root_dir);
-    println!("Running day 24");
-    day24::run(root_dir);
-    println!("Running day 25");
-    day25::run(root_dir);
+    if num_failed > 0 {
+        eprintln!("{} day(s) failed", num_failed);
+        std::process::exit(1);
+    }
+
+    if options.verify && num_mismatches > 0 {
+        eprintln!("{} answer(s) don't match {}", num_mismatches, answers_path.unwrap().display());
+        std::process::exit(1);
+    }
+
+    if options.bless {
+        let mut known = known_answers.unwrap();
+        for (day, part, answer) in &blessed {
+            known.record(*day, *part, answer);
+        }
+
+        let path = answers_path.unwrap();
+        if let Err(err) = std::fs::write(&path, known.to_file_string()) {
+            eprintln!("Failed to write answers to {}: {}", path.display(), err);
+            std::process::exit(1);
+        }
+        // Keep stdout machine readable for JSON output.
+        let message = format!("Recorded {} answer(s) in {}", blessed.len(), path.display());
+        match options.format {
+            Format::Text => println!("{}", message),
+            Format::Json => eprintln!("{}", message),
+        }
+    }
 }
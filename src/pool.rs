@@ -0,0 +1,110 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+
+/// The message a panic was raised with, if it was a string.
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
+
+/// Runs `work` on every item using up to `num_threads` threads pulling from a shared queue.
+/// Results are handed to `on_result` along with their item in the order of `items`, as soon as
+/// all earlier ones are done. A panicking item hands over the panic message instead, and the
+/// rest keep going.
+pub fn run_ordered<T, R, W, F>(items: &[T], num_threads: usize, work: W, mut on_result: F)
+where
+    T: Sync,
+    R: Send,
+    W: Fn(&T) -> R + Sync,
+    F: FnMut(&T, Result<R, String>),
+{
+    let num_threads = num_threads.clamp(1, items.len().max(1));
+    let queue = Mutex::new(items.iter().enumerate());
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..num_threads {
+            let sender = sender.clone();
+            let (queue, work) = (&queue, &work);
+            scope.spawn(move || loop {
+                // Take the lock only long enough to grab the next item.
+                let next = queue.lock().unwrap().next();
+                let Some((idx, item)) = next else {
+                    break;
+                };
+
+                let result = panic::catch_unwind(AssertUnwindSafe(|| work(item))).map_err(panic_message);
+                if sender.send((idx, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending = BTreeMap::new();
+        let mut next_idx = 0;
+        for (idx, result) in receiver {
+            pending.insert(idx, result);
+            while let Some(result) = pending.remove(&next_idx) {
+                on_result(&items[next_idx], result);
+                next_idx += 1;
+            }
+        }
+    });
+}
+
+/// The number of threads to use when asked for one per CPU.
+pub fn default_num_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn results_in_order() {
+        // Earlier items take longer, so they finish last.
+        let items = (0..8u64).collect::<Vec<_>>();
+        let mut results = Vec::new();
+        run_ordered(&items, 4, |&x| {
+            thread::sleep(Duration::from_millis(8 - x));
+            x * 10
+        }, |_, r| results.push(r.unwrap()));
+
+        assert_eq!(vec![0, 10, 20, 30, 40, 50, 60, 70], results);
+    }
+
+    #[test]
+    fn single_thread_and_empty() {
+        let mut results = Vec::new();
+        run_ordered(&[1, 2, 3], 1, |x| x + 1, |_, r| results.push(r.unwrap()));
+        assert_eq!(vec![2, 3, 4], results);
+
+        let mut count = 0;
+        run_ordered(&[] as &[u32], 4, |x| *x, |_, _| count += 1);
+        assert_eq!(0, count);
+    }
+
+    #[test]
+    fn panics() {
+        let mut results = Vec::new();
+        run_ordered(&[1, 0, 2, 0, 3], 2, |&x| {
+            assert!(x != 0, "item {} is zero", x);
+            x
+        }, |&item, r| results.push((item, r)));
+
+        assert_eq!(vec![(1, Ok(1)), (0, Err("item 0 is zero".to_string())), (2, Ok(2)),
+                        (0, Err("item 0 is zero".to_string())), (3, Ok(3))], results);
+    }
+}
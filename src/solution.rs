@@ -0,0 +1,166 @@
+use super::common;
+use super::error::{AocError, Result};
+use std::fmt;
+use std::io::BufRead;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Selects which parts of a day's puzzle should be solved.
+#[derive(Clone, Copy)]
+pub struct Parts {
+    pub one: bool,
+    pub two: bool,
+}
+
+impl Parts {
+    pub const BOTH: Parts = Parts { one: true, two: true };
+}
+
+/// The answer to one part of a puzzle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Int(i64),
+    Str(String),
+    /// A rendered grid with one line per row, for answers given as ASCII art.
+    Grid(String),
+    /// The puzzle has no answer for this part, like the free second star on day 25.
+    None,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Int(x) => write!(f, "{}", x),
+            Answer::Str(s) => write!(f, "{}", s),
+            Answer::Grid(grid) => write!(f, "\n{}", grid.trim_end()),
+            Answer::None => write!(f, "-"),
+        }
+    }
+}
+
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(x: $t) -> Answer {
+                Answer::Int(x.into())
+            }
+        })*
+    };
+}
+
+answer_from_int!(i32, u32, i64);
+
+/// Answers are signed 64 bit, so larger unsigned results have no answer we can give.
+macro_rules! answer_try_from_int {
+    ($($t:ty),*) => {
+        $(impl TryFrom<$t> for Answer {
+            type Error = AocError;
+
+            fn try_from(x: $t) -> Result<Answer> {
+                i64::try_from(x)
+                    .map(Answer::Int)
+                    .map_err(|_| AocError::no_solution(format!("The answer {} does not fit into an i64", x)))
+            }
+        })*
+    };
+}
+
+answer_try_from_int!(u64, usize, u128);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Answer {
+        Answer::Str(s)
+    }
+}
+
+/// A day's puzzle, split into parsing the input and solving each part on it.
+pub trait Solution {
+    type Input;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+    fn part1(input: &Self::Input) -> Result<Answer>;
+    fn part2(input: &Self::Input) -> Result<Answer>;
+}
+
+/// How long each phase of a day took. Reading the input file is not included.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PhaseTimes {
+    pub parse: Duration,
+    pub part1: Option<Duration>,
+    pub part2: Option<Duration>,
+}
+
+/// The answers a day produced for the selected parts.
+#[derive(Debug)]
+pub struct DayAnswers {
+    pub part1: Option<Answer>,
+    pub part2: Option<Answer>,
+    pub times: PhaseTimes,
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+/// Parses the input file and solves the selected parts with it.
+pub fn run<S: Solution>(input_path: &Path, parts: Parts) -> Result<DayAnswers> {
+    let input_bytes = common::read_input_bytes(input_path)?;
+    run_str::<S>(&String::from_utf8_lossy(&input_bytes), parts)
+}
+
+/// Reads the whole input from a stream and solves the selected parts with it.
+pub fn run_reader<S: Solution>(reader: impl BufRead, parts: Parts) -> Result<DayAnswers> {
+    run_str::<S>(&common::read_input_string(reader)?, parts)
+}
+
+/// Parses the input and solves the selected parts with it.
+pub fn run_str<S: Solution>(input: &str, parts: Parts) -> Result<DayAnswers> {
+    let (input, parse) = timed(|| S::parse(input));
+    let input = input?;
+
+    let solve_part = |solve: fn(&S::Input) -> Result<Answer>| {
+        let (answer, duration) = timed(|| solve(&input));
+        answer.map(|answer| (answer, duration))
+    };
+    let part1 = parts.one.then(|| solve_part(S::part1)).transpose()?;
+    let part2 = parts.two.then(|| solve_part(S::part2)).transpose()?;
+
+    Ok(DayAnswers {
+        times: PhaseTimes {
+            parse,
+            part1: part1.as_ref().map(|(_, t)| *t),
+            part2: part2.as_ref().map(|(_, t)| *t),
+        },
+        part1: part1.map(|(answer, _)| answer),
+        part2: part2.map(|(answer, _)| answer),
+    })
+}
+
+/// Parses an example input and solves both parts, panicking on errors. Used by the day tests.
+#[cfg(test)]
+pub fn solve_example<S: Solution>(input: &str) -> (Answer, Answer) {
+    let input = S::parse(input).unwrap();
+    (S::part1(&input).unwrap(), S::part2(&input).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answer_display() {
+        assert_eq!("-12", Answer::from(-12i32).to_string());
+        assert_eq!("abc", Answer::from("abc".to_string()).to_string());
+        assert_eq!("\n* *\n *", Answer::Grid("* *\n * \n".to_string()).to_string());
+        assert_eq!("-", Answer::None.to_string());
+    }
+
+    #[test]
+    fn answer_from_unsigned() {
+        assert_eq!(Answer::Int(i64::MAX), Answer::try_from(i64::MAX as u64).unwrap());
+        assert!(matches!(Answer::try_from(u64::MAX), Err(AocError::NoSolution { .. })));
+        assert!(matches!(Answer::try_from(1u128 << 64), Err(AocError::NoSolution { .. })));
+    }
+}
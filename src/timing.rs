@@ -0,0 +1,147 @@
+use super::solution::PhaseTimes;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// Median phase times of a day over all of its runs.
+pub struct DayTiming {
+    pub day: u32,
+    pub runs: usize,
+    pub times: PhaseTimes,
+}
+
+impl DayTiming {
+    /// Builds the timing for a day from the phase times of each of its runs.
+    pub fn from_runs(day: u32, runs: &[PhaseTimes]) -> DayTiming {
+        assert!(!runs.is_empty());
+
+        let median_of = |phase: fn(&PhaseTimes) -> Option<Duration>| {
+            let mut samples = runs.iter().filter_map(phase).collect::<Vec<_>>();
+            (!samples.is_empty()).then(|| median(&mut samples))
+        };
+
+        DayTiming {
+            day,
+            runs: runs.len(),
+            times: PhaseTimes {
+                parse: median_of(|t| Some(t.parse)).unwrap(),
+                part1: median_of(|t| t.part1),
+                part2: median_of(|t| t.part2),
+            },
+        }
+    }
+
+    pub fn total(&self) -> Duration {
+        self.times.parse + self.times.part1.unwrap_or_default() + self.times.part2.unwrap_or_default()
+    }
+}
+
+/// Returns the median of the samples, averaging the middle two for an even count.
+pub fn median(samples: &mut [Duration]) -> Duration {
+    assert!(!samples.is_empty());
+    samples.sort_unstable();
+
+    let mid = samples.len() / 2;
+    if samples.len().is_multiple_of(2) {
+        (samples[mid - 1] + samples[mid]) / 2
+    } else {
+        samples[mid]
+    }
+}
+
+pub fn millis(duration: Duration) -> f64 {
+    duration.as_nanos() as f64 / 1_000_000.0
+}
+
+fn format_millis(duration: Option<Duration>) -> String {
+    match duration {
+        Some(d) => format!("{:.3}", millis(d)),
+        None => "-".to_string(),
+    }
+}
+
+fn add_phase(total: Option<Duration>, phase: Option<Duration>) -> Option<Duration> {
+    match phase {
+        Some(d) => Some(total.unwrap_or_default() + d),
+        None => total,
+    }
+}
+
+/// Prints a table with one row per day and a row with the totals, all in milliseconds.
+pub fn print_table(timings: &[DayTiming]) {
+    println!("{:>4} {:>5} {:>12} {:>12} {:>12} {:>12}", "Day", "Runs", "Parse ms", "Part 1 ms", "Part 2 ms", "Total ms");
+
+    let mut totals = PhaseTimes::default();
+    for timing in timings {
+        println!("{:>4} {:>5} {:>12} {:>12} {:>12} {:>12}",
+                 timing.day,
+                 timing.runs,
+                 format_millis(Some(timing.times.parse)),
+                 format_millis(timing.times.part1),
+                 format_millis(timing.times.part2),
+                 format_millis(Some(timing.total())));
+
+        totals.parse += timing.times.parse;
+        totals.part1 = add_phase(totals.part1, timing.times.part1);
+        totals.part2 = add_phase(totals.part2, timing.times.part2);
+    }
+
+    let total = timings.iter().map(|t| t.total()).sum();
+    println!("{:>4} {:>5} {:>12} {:>12} {:>12} {:>12}",
+             "All",
+             "",
+             format_millis(Some(totals.parse)),
+             format_millis(totals.part1),
+             format_millis(totals.part2),
+             format_millis(Some(total)));
+}
+
+/// Writes the timings as CSV in milliseconds. Parts that weren't run are left empty.
+pub fn write_csv(mut writer: impl Write, timings: &[DayTiming]) -> io::Result<()> {
+    let csv_millis = |duration: Option<Duration>| {
+        duration.map(|d| format!("{:.3}", millis(d))).unwrap_or_default()
+    };
+
+    writeln!(writer, "day,runs,parse_ms,part1_ms,part2_ms,total_ms")?;
+    for timing in timings {
+        writeln!(writer, "{},{},{},{},{},{}",
+                 timing.day,
+                 timing.runs,
+                 csv_millis(Some(timing.times.parse)),
+                 csv_millis(timing.times.part1),
+                 csv_millis(timing.times.part2),
+                 csv_millis(Some(timing.total())))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(x: u64) -> Duration {
+        Duration::from_millis(x)
+    }
+
+    #[test]
+    fn median_odd_and_even() {
+        assert_eq!(ms(3), median(&mut [ms(5), ms(1), ms(3)]));
+        assert_eq!(ms(4), median(&mut [ms(5), ms(1), ms(3), ms(9)]));
+        assert_eq!(ms(7), median(&mut [ms(7)]));
+    }
+
+    #[test]
+    fn csv_output() {
+        let runs = [
+            PhaseTimes { parse: ms(1), part1: Some(ms(2)), part2: None },
+            PhaseTimes { parse: ms(3), part1: Some(ms(4)), part2: None },
+            PhaseTimes { parse: ms(2), part1: Some(ms(9)), part2: None },
+        ];
+        let timings = [DayTiming::from_runs(6, &runs)];
+
+        let mut out = Vec::new();
+        write_csv(&mut out, &timings).unwrap();
+
+        assert_eq!("day,runs,parse_ms,part1_ms,part2_ms,total_ms\n6,3,2.000,4.000,,6.000\n",
+                   String::from_utf8(out).unwrap());
+    }
+}